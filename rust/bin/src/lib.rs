@@ -7,6 +7,9 @@ struct MyExtension;
 #[gdextension]
 unsafe impl ExtensionLibrary for MyExtension {
     fn on_stage_init(stage: InitStage) {
+        if stage == InitStage::Scene {
+            tools::logger::init();
+        }
         if stage == InitStage::MainLoop {
             godot_print!("InitStage: Scene");
             ToastManager::setup_toast_manager();
@@ -6,4 +6,33 @@ pub enum MyError {
     NetworkCode(i64,String),
     #[error("Json Parse Failed, error: {0}")]
     JsonParse(String),
+    /// Torn API 错误码 1、2
+    #[error("Torn API key is invalid: {0}")]
+    TornInvalidKey(String),
+    /// Torn API 错误码 16
+    #[error("Torn API key access level is too low: {0}")]
+    TornAccessLevel(String),
+    /// Torn API 错误码 5
+    #[error("Torn API too many requests: {0}")]
+    TornTooManyRequests(String),
+    /// Torn API 错误码 13、18
+    #[error("Torn API key is paused: {0}")]
+    TornKeyPaused(String),
+    /// 其他 Torn API 错误
+    #[error("Torn API error, code: {0}, error: {1}")]
+    TornApi(i64, String),
+    /// weav3r 返回的错误行，digest 和 message
+    #[error("weav3r server error, digest: {0}, message: {1}")]
+    Weav3rFlight(String, String),
+    /// next-action id 已经失效，需要重新解析 curl
+    #[error("weav3r next-action is outdated: {0}")]
+    Weav3rActionOutdated(String),
+    /// Cloudflare 验证页面，cf_clearance 可能已经过期
+    #[error("weav3r Cloudflare challenge: {0}")]
+    Weav3rCloudflare(String),
+    /// 请求过于频繁，Retry-After 秒数，没有时为 0
+    #[error("weav3r rate limited, retry after: {0}s")]
+    Weav3rRateLimited(u64),
+    #[error("weav3r server error, code: {0}, body: {1}")]
+    Weav3rServer(i64, String),
 }
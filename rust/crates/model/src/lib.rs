@@ -1,4 +1,5 @@
 pub mod user_log;
 pub mod error;
 pub mod weav3r;
-pub mod items;
\ No newline at end of file
+pub mod items;
+pub mod torn;
\ No newline at end of file
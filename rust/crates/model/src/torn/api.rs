@@ -0,0 +1,165 @@
+use serde::{Deserialize, de::DeserializeOwned};
+use tools::http::url::build_url;
+
+use crate::error::MyError;
+
+pub const TORN_API_V2_URL: &str = "https://api.torn.com/v2";
+
+/// Torn API 错误码
+pub const TORN_ERROR_KEY_EMPTY: i64 = 1;
+pub const TORN_ERROR_INCORRECT_KEY: i64 = 2;
+pub const TORN_ERROR_TOO_MANY_REQUESTS: i64 = 5;
+pub const TORN_ERROR_KEY_DISABLED: i64 = 13;
+pub const TORN_ERROR_ACCESS_LEVEL: i64 = 16;
+pub const TORN_ERROR_KEY_PAUSED: i64 = 18;
+
+/// Torn API 请求，key 通过 Authorization 头传入，不放在地址中
+pub trait TornApiRequest {
+    type Response: DeserializeOwned;
+
+    /// v2 接口路径，例如 `/user/log`
+    fn path(&self) -> String;
+
+    /// 查询参数
+    fn query(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// 完整请求地址，查询参数按顺序编码
+    fn url(&self) -> String {
+        let query: Vec<(String, String)> = self
+            .query()
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        build_url(&format!("{}{}", TORN_API_V2_URL, self.path()), &query)
+    }
+}
+
+/// Torn API 错误响应 `{"error":{"code":2,"error":"Incorrect key"}}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TornErrorResponse {
+    pub error: TornError,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TornError {
+    pub code: i64,
+    pub error: String,
+}
+
+impl From<TornError> for MyError {
+    fn from(e: TornError) -> Self {
+        match e.code {
+            TORN_ERROR_KEY_EMPTY | TORN_ERROR_INCORRECT_KEY => MyError::TornInvalidKey(e.error),
+            TORN_ERROR_ACCESS_LEVEL => MyError::TornAccessLevel(e.error),
+            TORN_ERROR_TOO_MANY_REQUESTS => MyError::TornTooManyRequests(e.error),
+            TORN_ERROR_KEY_DISABLED | TORN_ERROR_KEY_PAUSED => MyError::TornKeyPaused(e.error),
+            code => MyError::TornApi(code, e.error),
+        }
+    }
+}
+
+/// 解析 Torn API 响应，错误响应的状态码也可能是 200，所以先检查错误结构
+pub fn parse_torn_response<T: DeserializeOwned>(
+    response_code: i64,
+    text: &str,
+) -> Result<T, MyError> {
+    if let Ok(resp) = serde_json::from_str::<TornErrorResponse>(text) {
+        return Err(resp.error.into());
+    }
+    if response_code != 200 {
+        return Err(MyError::NetworkCode(
+            response_code,
+            String::from("Failed to get response."),
+        ));
+    }
+    serde_json::from_str(text).map_err(|e| MyError::JsonParse(e.to_string()))
+}
+
+#[cfg(test)]
+mod api_test {
+    use super::*;
+    use crate::{
+        torn::{item_market::ItemMarketReq, items::TornItemsReq, points_market::PointsMarketReq},
+        user_log::UserLogReq,
+    };
+
+    #[test]
+    fn test_url() {
+        let req = UserLogReq {
+            limit: 20,
+            cat: Some(94),
+            ..Default::default()
+        };
+        assert_eq!(
+            req.url(),
+            "https://api.torn.com/v2/user/log?limit=20&cat=94"
+        );
+        assert_eq!(
+            ItemMarketReq { item_id: 206 }.url(),
+            "https://api.torn.com/v2/market/206/itemmarket"
+        );
+        assert_eq!(
+            TornItemsReq::default().url(),
+            "https://api.torn.com/v2/torn/items"
+        );
+        assert_eq!(
+            TornItemsReq { ids: vec![1, 206] }.url(),
+            "https://api.torn.com/v2/torn/1,206/items"
+        );
+        assert_eq!(
+            PointsMarketReq.url(),
+            "https://api.torn.com/v2/market?selections=pointsmarket"
+        );
+
+        // 查询参数需要编码
+        struct SearchReq;
+        impl TornApiRequest for SearchReq {
+            type Response = ();
+            fn path(&self) -> String {
+                "/torn/search".to_string()
+            }
+            fn query(&self) -> Vec<(&'static str, String)> {
+                vec![("q", "a b&c=d".to_string()), ("q", "é".to_string())]
+            }
+        }
+        assert_eq!(
+            SearchReq.url(),
+            "https://api.torn.com/v2/torn/search?q=a%20b%26c%3Dd&q=%C3%A9"
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let parse = |code: i64| {
+            let text = format!(r#"{{"error":{{"code":{},"error":"msg"}}}}"#, code);
+            parse_torn_response::<<UserLogReq as TornApiRequest>::Response>(200, &text).unwrap_err()
+        };
+        assert!(matches!(parse(2), MyError::TornInvalidKey(_)));
+        assert!(matches!(parse(16), MyError::TornAccessLevel(_)));
+        assert!(matches!(parse(5), MyError::TornTooManyRequests(_)));
+        assert!(matches!(parse(18), MyError::TornKeyPaused(_)));
+        assert!(matches!(parse(9), MyError::TornApi(9, _)));
+
+        let res = parse_torn_response::<<UserLogReq as TornApiRequest>::Response>(502, "bad");
+        assert!(matches!(res, Err(MyError::NetworkCode(502, _))));
+        let res = parse_torn_response::<<UserLogReq as TornApiRequest>::Response>(200, "{}");
+        assert!(matches!(res, Err(MyError::JsonParse(_))));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let text = include_str!("../../fixtures/torn_items.json");
+        let items = parse_torn_response::<<TornItemsReq as TornApiRequest>::Response>(200, text)
+            .unwrap()
+            .items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].name, "Xanax");
+        assert_eq!(items[0].value.market_price, 830000);
+        let info = items[1].to_item_info();
+        assert_eq!(info.t, "Melee");
+        assert_eq!(info.weapon_type, "Clubbing");
+        assert!(info.tradeable);
+    }
+}
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+use crate::{error::MyError, torn::api::TornApiRequest};
+
+/// Torn API v2 `market/{id}/itemmarket` 请求
+#[derive(Debug, Clone, Copy)]
+pub struct ItemMarketReq {
+    pub item_id: i32,
+}
+
+impl TornApiRequest for ItemMarketReq {
+    type Response = ItemMarketResponse;
+
+    fn path(&self) -> String {
+        format!("/market/{}/itemmarket", self.item_id)
+    }
+}
+
+/// Torn API v2 `market/{id}/itemmarket` 响应
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemMarketResponse {
+    pub itemmarket: ItemMarket,
+}
+
+impl ItemMarketResponse {
+    pub fn from_json(json: &str) -> Result<Self, MyError> {
+        serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemMarket {
+    pub item: ItemMarketItem,
+    /// 按价格从低到高排列
+    #[serde(default)]
+    pub listings: Vec<ItemMarketListing>,
+    #[serde(default)]
+    pub cache_timestamp: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemMarketItem {
+    pub id: i32,
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub item_type: String,
+    #[serde(default)]
+    pub average_price: Option<i64>,
+}
+
+/// 物品市场挂单，不包含卖家信息
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemMarketListing {
+    pub price: i64,
+    pub amount: i32,
+}
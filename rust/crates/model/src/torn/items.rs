@@ -0,0 +1,87 @@
+use serde::Deserialize;
+
+use crate::{items::ItemInfo, torn::api::TornApiRequest};
+
+/// Torn API v2 `torn/{ids}/items` 请求，ids 为空时返回全部物品
+#[derive(Debug, Clone, Default)]
+pub struct TornItemsReq {
+    pub ids: Vec<i32>,
+}
+
+impl TornApiRequest for TornItemsReq {
+    type Response = TornItemsResponse;
+
+    fn path(&self) -> String {
+        if self.ids.is_empty() {
+            return "/torn/items".to_string();
+        }
+        let ids = self
+            .ids
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        format!("/torn/{}/items", ids)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TornItemsResponse {
+    pub items: Vec<TornItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TornItem {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub effect: Option<String>,
+    #[serde(default)]
+    pub requirement: Option<String>,
+    #[serde(default)]
+    pub image: String,
+    #[serde(rename = "type", default)]
+    pub item_type: String,
+    /// 武器时为武器类型
+    #[serde(default)]
+    pub sub_type: Option<String>,
+    #[serde(default)]
+    pub is_tradable: bool,
+    #[serde(default)]
+    pub value: TornItemValue,
+    #[serde(default)]
+    pub circulation: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TornItemValue {
+    #[serde(default)]
+    pub buy_price: Option<u64>,
+    #[serde(default)]
+    pub sell_price: Option<u64>,
+    #[serde(default)]
+    pub market_price: u64,
+}
+
+impl TornItem {
+    /// 转换为内置物品列表的结构
+    pub fn to_item_info(&self) -> ItemInfo {
+        ItemInfo {
+            id: self.id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            effect: self.effect.clone().unwrap_or_default(),
+            requirement: self.requirement.clone().unwrap_or_default(),
+            t: self.item_type.clone(),
+            weapon_type: self.sub_type.clone().unwrap_or_default(),
+            buy_price: self.value.buy_price.unwrap_or_default(),
+            sell_price: self.value.sell_price.unwrap_or_default(),
+            market_value: self.value.market_price,
+            circulation: self.circulation,
+            image: self.image.clone(),
+            tradeable: self.is_tradable,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod api;
+pub mod item_market;
+pub mod items;
+pub mod points_market;
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{error::MyError, torn::api::TornApiRequest};
+
+/// Torn API v2 `market?selections=pointsmarket` 请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PointsMarketReq;
+
+impl TornApiRequest for PointsMarketReq {
+    type Response = PointsMarketResponse;
+
+    fn path(&self) -> String {
+        "/market".to_string()
+    }
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        vec![("selections", "pointsmarket".to_string())]
+    }
+}
+
+/// Torn API `market?selections=pointsmarket` 响应，key 为挂单 id
+#[derive(Debug, Clone, Deserialize)]
+pub struct PointsMarketResponse {
+    #[serde(default)]
+    pub pointsmarket: HashMap<String, PointsListing>,
+}
+
+impl PointsMarketResponse {
+    pub fn from_json(json: &str) -> Result<Self, MyError> {
+        serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+
+    /// 最低单价
+    pub fn lowest_cost(&self) -> Option<u64> {
+        self.pointsmarket.values().map(|x| x.cost).min()
+    }
+}
+
+/// 点数挂单
+#[derive(Debug, Clone, Deserialize)]
+pub struct PointsListing {
+    /// 单价
+    pub cost: u64,
+    pub quantity: u64,
+    pub total_cost: u64,
+}
@@ -1,14 +1,17 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::error::MyError;
+use crate::{error::MyError, torn::api::TornApiRequest};
 
 pub struct UserLogReq {
     pub target: Option<u32>,
     pub limit: u32,
     // 分类
     pub cat: Option<u32>,
-    pub key: String,
+    /// 只返回这个时间之后的日志
+    pub from: Option<i64>,
+    /// 只返回这个时间之前的日志
+    pub to: Option<i64>,
 }
 
 impl Default for UserLogReq {
@@ -17,11 +20,37 @@ impl Default for UserLogReq {
             target: None,
             limit: 10,
             cat: None,
-            key: String::new(),
+            from: None,
+            to: None,
         }
     }
 }
 
+impl TornApiRequest for UserLogReq {
+    type Response = UserLogResponse;
+
+    fn path(&self) -> String {
+        "/user/log".to_string()
+    }
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("limit", self.limit.to_string())];
+        if let Some(target) = self.target {
+            query.push(("target", target.to_string()));
+        }
+        if let Some(cat) = self.cat {
+            query.push(("cat", cat.to_string()));
+        }
+        if let Some(from) = self.from {
+            query.push(("from", from.to_string()));
+        }
+        if let Some(to) = self.to {
+            query.push(("to", to.to_string()));
+        }
+        query
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct UserLogResponse {
     pub log: Vec<UserLogEntry>,
@@ -33,7 +62,7 @@ impl UserLogResponse {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserLogEntry {
     pub id: String,
     pub timestamp: i64,
@@ -42,17 +71,46 @@ pub struct UserLogEntry {
     pub params: Value,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserLogDetails {
     pub id: u32,
     pub title: String,
     pub category: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserLogData {
+    /// 交易对象，只有交易日志存在
+    #[serde(default)]
     pub user: i64,
     pub money: Option<u64>,
     pub total: Option<u64>,
     pub description: Option<String>,
+    /// 买入日志的卖家
+    #[serde(default)]
+    pub seller: Option<i64>,
+    /// 卖出日志的买家
+    #[serde(default)]
+    pub buyer: Option<i64>,
+    /// 买卖的物品
+    #[serde(default)]
+    pub items: Vec<UserLogItem>,
+    #[serde(default)]
+    pub cost_each: Option<u64>,
+    #[serde(default)]
+    pub cost_total: Option<u64>,
+    /// 物品市场出售费用
+    #[serde(default)]
+    pub fee: Option<u64>,
+    /// 商店出售总价
+    #[serde(default)]
+    pub total_value: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserLogItem {
+    pub id: i32,
+    #[serde(default)]
+    pub uid: Option<i64>,
+    pub qty: u64,
 }
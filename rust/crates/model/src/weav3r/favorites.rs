@@ -1,5 +1,6 @@
-use crate::error::MyError;
+use crate::{error::MyError, weav3r::flight::FlightStream};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fmt;
 
 #[derive(Debug, Clone, Default)]
@@ -8,21 +9,33 @@ pub struct FavoritesResponse {
 }
 
 impl FavoritesResponse {
+    /// 解析 weav3r 返回的 flight 流
+    /// 收藏列表按结构查找：元素包含 cheapestBazaars 的数组，列表为空时使用 action 的返回值
     pub fn from_text(text: &str) -> Result<Self, MyError> {
-        for line in text.lines() {
-            if let Some(colon_pos) = line.find(':') {
-                let index = &line[..colon_pos];
-                let json_part = &line[colon_pos + 1..];
-
-                if index != "1" {
-                    continue;
-                }
-                let item: Vec<ProductionItem> = serde_json::from_str(json_part)
-                    .map_err(|e| MyError::JsonParse(e.to_string()))?;
-                return Ok(Self { items: item });
+        let stream = FlightStream::parse(text)?;
+        let payload = stream.find(Self::is_favorites).or_else(|| {
+            stream
+                .resolve(0)
+                .and_then(|root| root.get("a").cloned())
+                .filter(|x| x.as_array().is_some_and(|x| x.is_empty()))
+        });
+        let Some(payload) = payload else {
+            if let Some(error) = stream.errors().next() {
+                return Err(error.clone().into());
             }
-        }
-        Ok(Self::default())
+            return Err(MyError::JsonParse(
+                "favorites payload not found".to_string(),
+            ));
+        };
+        let items: Vec<ProductionItem> =
+            serde_json::from_value(payload).map_err(|e| MyError::JsonParse(e.to_string()))?;
+        Ok(Self { items })
+    }
+
+    fn is_favorites(value: &Value) -> bool {
+        value.as_array().is_some_and(|items| {
+            !items.is_empty() && items.iter().all(|x| x.get("cheapestBazaars").is_some())
+        })
     }
 }
 
@@ -72,3 +85,49 @@ impl fmt::Display for FavoritesResponse {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod favorites_test {
+    use super::*;
+
+    #[test]
+    fn test_mock_server_response() {
+        let text = include_str!("../../fixtures/weav3r_favorites_resp.txt");
+        let res = FavoritesResponse::from_text(text).unwrap();
+        assert_eq!(res.items.len(), 103);
+        assert_eq!(res.items[0].id, 385);
+        assert_eq!(res.items[0].cheapest_bazaars[0].player_id, 2828142);
+        assert_eq!(res.items[102].name, "Melatonin");
+    }
+
+    #[test]
+    fn test_payload_by_structure() {
+        // 收藏列表不在第 1 行，并且前面有文本行和模块行
+        let text = "0:{\"a\":\"$@3\",\"f\":\"\"}\n\
+            1:T5,hello\
+            2:I[\"chunk\",[]]\n\
+            3:[{\"id\":206,\"name\":\"Xanax\",\"image\":\"\",\"marketPrice\":null,\
+            \"avgBazaarPrice\":800000,\"cheapestBazaars\":[]}]\n";
+        let res = FavoritesResponse::from_text(text).unwrap();
+        assert_eq!(res.items.len(), 1);
+        assert_eq!(res.items[0].id, 206);
+        assert_eq!(res.items[0].market_price, None);
+
+        // 空收藏
+        let res = FavoritesResponse::from_text("0:{\"a\":\"$@1\"}\n1:[]\n").unwrap();
+        assert!(res.items.is_empty());
+    }
+
+    #[test]
+    fn test_error_row() {
+        let text = "0:{\"a\":\"$@1\"}\n1:E{\"digest\":\"2394\",\"message\":\"failed\"}\n";
+        match FavoritesResponse::from_text(text) {
+            Err(MyError::Weav3rFlight(digest, message)) => {
+                assert_eq!(digest, "2394");
+                assert_eq!(message, "failed");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(FavoritesResponse::from_text("<html></html>").is_err());
+    }
+}
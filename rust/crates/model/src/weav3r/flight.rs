@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::MyError;
+
+/// 引用解析的最大深度，避免循环引用
+const MAX_RESOLVE_DEPTH: usize = 32;
+
+/// React Server Components flight 流中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlightRow {
+    /// JSON 数据行 `1:{...}`
+    Json(Value),
+    /// 文本行 `1:T长度,内容`，长度为十六进制字节数，内容可以包含换行
+    Text(String),
+    /// 错误行 `1:E{...}`
+    Error(FlightError),
+    /// 其他带标记的行，例如 `I` 模块引用、`H` 提示
+    Other { tag: char, payload: String },
+}
+
+/// 服务端返回的错误，生产环境通常只有 digest
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FlightError {
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl From<FlightError> for MyError {
+    fn from(e: FlightError) -> Self {
+        MyError::Weav3rFlight(e.digest.unwrap_or_default(), e.message.unwrap_or_default())
+    }
+}
+
+/// 解析后的 flight 流，行 id 为十六进制
+#[derive(Debug, Clone, Default)]
+pub struct FlightStream {
+    /// 按出现顺序保存
+    rows: Vec<(u32, FlightRow)>,
+    index: HashMap<u32, usize>,
+}
+
+impl FlightStream {
+    /// 按行切分，文本行按长度读取，格式不正确时返回错误
+    pub fn parse(text: &str) -> Result<Self, MyError> {
+        let mut res = Self::default();
+        let mut rest = text;
+        loop {
+            rest = rest.trim_start_matches(['\n', '\r']);
+            if rest.is_empty() {
+                break;
+            }
+            let (id, payload) = rest
+                .split_once(':')
+                .and_then(|(id, payload)| Some((u32::from_str_radix(id, 16).ok()?, payload)))
+                .ok_or_else(|| Self::invalid(rest))?;
+            let (row, next) = Self::parse_row(payload)?;
+            res.push(id, row);
+            rest = next;
+        }
+        Ok(res)
+    }
+
+    fn invalid(rest: &str) -> MyError {
+        let line = rest.lines().next().unwrap_or_default();
+        let line: String = line.chars().take(100).collect();
+        MyError::JsonParse(format!("invalid flight row: {}", line))
+    }
+
+    /// 解析一行的内容，返回剩余的文本
+    fn parse_row(payload: &str) -> Result<(FlightRow, &str), MyError> {
+        if let Some(text) = payload.strip_prefix('T') {
+            let (len, text) = text.split_once(',').ok_or_else(|| Self::invalid(payload))?;
+            let len = usize::from_str_radix(len, 16).map_err(|_| Self::invalid(payload))?;
+            if text.len() < len || !text.is_char_boundary(len) {
+                return Err(MyError::JsonParse(format!(
+                    "flight text row is shorter than {} bytes",
+                    len
+                )));
+            }
+            let (text, next) = text.split_at(len);
+            return Ok((FlightRow::Text(text.to_string()), next));
+        }
+
+        let (line, next) = payload.split_once('\n').unwrap_or((payload, ""));
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let row = match line.chars().next() {
+            Some('E') => {
+                let error = serde_json::from_str(&line[1..])
+                    .map_err(|e| MyError::JsonParse(e.to_string()))?;
+                FlightRow::Error(error)
+            }
+            Some(tag) if tag.is_ascii_uppercase() => FlightRow::Other {
+                tag,
+                payload: line[1..].to_string(),
+            },
+            _ => FlightRow::Json(
+                serde_json::from_str(line).map_err(|e| MyError::JsonParse(e.to_string()))?,
+            ),
+        };
+        Ok((row, next))
+    }
+
+    /// 同一个 id 出现多次时使用最后一行
+    fn push(&mut self, id: u32, row: FlightRow) {
+        match self.index.get(&id) {
+            Some(i) => self.rows[*i].1 = row,
+            None => {
+                self.index.insert(id, self.rows.len());
+                self.rows.push((id, row));
+            }
+        }
+    }
+
+    pub fn rows(&self) -> &[(u32, FlightRow)] {
+        &self.rows
+    }
+
+    pub fn row(&self, id: u32) -> Option<&FlightRow> {
+        self.index.get(&id).map(|i| &self.rows[*i].1)
+    }
+
+    /// 所有错误行
+    pub fn errors(&self) -> impl Iterator<Item = &FlightError> {
+        self.rows.iter().filter_map(|(_, row)| match row {
+            FlightRow::Error(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// 解析指定行，数据中的 `$@n`、`$n` 引用替换为对应行的内容
+    pub fn resolve(&self, id: u32) -> Option<Value> {
+        self.resolve_row(id, 0)
+    }
+
+    fn resolve_row(&self, id: u32, depth: usize) -> Option<Value> {
+        if depth > MAX_RESOLVE_DEPTH {
+            return None;
+        }
+        match self.row(id)? {
+            FlightRow::Json(value) => Some(self.resolve_value(value, depth + 1)),
+            FlightRow::Text(text) => Some(Value::String(text.clone())),
+            _ => None,
+        }
+    }
+
+    fn resolve_value(&self, value: &Value, depth: usize) -> Value {
+        match value {
+            Value::String(s) => self.resolve_str(s, depth),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|x| self.resolve_value(x, depth)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.resolve_value(v, depth)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    /// `$$` 开头为转义的 `$`，`$undefined` 为空，`$@n`、`$Ln`、`$n` 为行引用，
+    /// 行引用后可以跟 `:key:key` 路径，无法解析的引用保留原文
+    fn resolve_str(&self, s: &str, depth: usize) -> Value {
+        let Some(reference) = s.strip_prefix('$') else {
+            return Value::String(s.to_string());
+        };
+        if reference.starts_with('$') {
+            return Value::String(reference.to_string());
+        }
+        if reference == "undefined" {
+            return Value::Null;
+        }
+        let reference = reference
+            .strip_prefix('@')
+            .or_else(|| reference.strip_prefix('L'))
+            .unwrap_or(reference);
+        let mut path = reference.split(':');
+        let Some(id) = path.next().and_then(|x| u32::from_str_radix(x, 16).ok()) else {
+            return Value::String(s.to_string());
+        };
+        let mut value = match self.resolve_row(id, depth) {
+            Some(value) => value,
+            None => return Value::String(s.to_string()),
+        };
+        for key in path {
+            value = match value {
+                Value::Object(mut map) => map.remove(key).unwrap_or_default(),
+                Value::Array(mut items) => match key.parse::<usize>() {
+                    Ok(i) if i < items.len() => items.swap_remove(i),
+                    _ => Value::Null,
+                },
+                _ => Value::Null,
+            };
+        }
+        value
+    }
+
+    /// 按顺序在所有数据行中查找第一个满足条件的值，引用已经解析
+    pub fn find(&self, pred: impl Fn(&Value) -> bool) -> Option<Value> {
+        self.rows.iter().find_map(|(id, row)| match row {
+            FlightRow::Json(_) => Self::find_in(self.resolve(*id)?, &pred),
+            _ => None,
+        })
+    }
+
+    fn find_in(value: Value, pred: &impl Fn(&Value) -> bool) -> Option<Value> {
+        if pred(&value) {
+            return Some(value);
+        }
+        match value {
+            Value::Array(items) => items.into_iter().find_map(|x| Self::find_in(x, pred)),
+            Value::Object(map) => map.into_values().find_map(|x| Self::find_in(x, pred)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod flight_test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rows() {
+        let text = "0:{\"a\":\"$@1\",\"t\":\"$2\",\"u\":\"$undefined\",\"d\":\"$$5\"}\n\
+            1:[{\"id\":1,\"n\":\"$3:name\"}]\n\
+            2:Tb,line1\nline2\
+            3:{\"name\":\"Hammer\"}\n\
+            4:I[\"chunk\",[]]\n\
+            5:E{\"digest\":\"123\"}\n";
+        let stream = FlightStream::parse(text).unwrap();
+        assert_eq!(stream.rows().len(), 6);
+        assert_eq!(
+            stream.row(2),
+            Some(&FlightRow::Text("line1\nline2".to_string()))
+        );
+        assert!(matches!(
+            stream.row(4),
+            Some(FlightRow::Other { tag: 'I', .. })
+        ));
+        assert_eq!(
+            stream.errors().cloned().collect::<Vec<_>>(),
+            vec![FlightError {
+                digest: Some("123".to_string()),
+                message: None,
+            }]
+        );
+        assert_eq!(
+            stream.resolve(0),
+            Some(json!({
+                "a": [{"id": 1, "n": "Hammer"}],
+                "t": "line1\nline2",
+                "u": null,
+                "d": "$5",
+            }))
+        );
+    }
+
+    #[test]
+    fn test_cyclic_and_invalid() {
+        // 循环引用在超过深度后保留原文，不会死循环
+        let stream = FlightStream::parse("0:[\"$1\"]\n1:[\"$0\"]\n").unwrap();
+        assert!(stream.resolve(0).is_some());
+
+        assert!(FlightStream::parse("<!DOCTYPE html><html></html>").is_err());
+        assert!(FlightStream::parse("0:T10,short").is_err());
+        assert!(FlightStream::parse("0:{bad json}\n").is_err());
+        assert!(FlightStream::parse("").unwrap().rows().is_empty());
+    }
+}
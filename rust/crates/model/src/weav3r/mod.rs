@@ -1 +1,2 @@
-pub mod favorites;
\ No newline at end of file
+pub mod favorites;
+pub mod flight;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use godot::{classes::HttpRequest, prelude::*};
+use model::{error::MyError, torn::item_market::ItemMarketReq};
+use torn_logic::rate_limit::RequestPriority;
+use weav3r::quote::{PriceQuote, TornItemMarketSource};
+
+use crate::node::http::torn_api::{acquire_torn_api, report_torn_api_result, torn_api_http};
+
+/// Torn API 物品市场挂单请求
+#[derive(GodotClass)]
+#[class(init, base=HttpRequest)]
+pub struct ItemMarketHttpRequest {
+    #[export]
+    type_name: GString,
+    #[base]
+    base: Base<HttpRequest>,
+    /// 正在请求的 key，用于记录限流结果
+    key: String,
+}
+
+impl ItemMarketHttpRequest {
+    /// 请求单个物品的物品市场挂单，正在请求时忽略
+    pub fn send_request(&mut self, item_id: i32, key: &str) -> bool {
+        if self.base().get_http_client_status() != godot::classes::http_client::Status::DISCONNECTED
+        {
+            godot_print!("ItemMarketHttpRequest: Already requesting.");
+            return false;
+        }
+
+        if key.is_empty() {
+            godot_warn!("ItemMarketHttpRequest: Key is empty.");
+            return false;
+        }
+
+        if !acquire_torn_api(key, "item_market", RequestPriority::Normal) {
+            return false;
+        }
+
+        let http = torn_api_http(&ItemMarketReq { item_id }, key);
+        if let Err(err) = http.send_request(&mut self.base_mut()) {
+            godot_error!("ItemMarketHttpRequest failed: {:?}", err);
+            return false;
+        }
+        self.key = key.to_string();
+        true
+    }
+
+    pub fn parse_response(
+        &self,
+        response_code: i64,
+        body: PackedByteArray,
+    ) -> Result<Vec<PriceQuote>, MyError> {
+        let response_text = String::from_utf8_lossy(body.as_slice());
+        let result = TornItemMarketSource.parse_response(
+            response_code,
+            &response_text,
+            tools::time::get_current_time(),
+        );
+        report_torn_api_result(&self.key, &result);
+        result.inspect_err(|e| {
+            godot_error!(
+                "ItemMarketHttpRequest: Failed to parse response: {:?},code: {},value: {}",
+                e,
+                response_code,
+                response_text
+            );
+        })
+    }
+}
@@ -1,3 +1,7 @@
 pub mod weav3r_favorites;
 pub mod user_log;
-pub mod image;
\ No newline at end of file
+pub mod image;
+pub mod item_market;
+pub mod points_market;
+pub mod torn_api;
+pub mod torn_items;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use godot::{classes::HttpRequest, prelude::*};
+use model::{error::MyError, torn::points_market::PointsMarketReq};
+use torn_logic::rate_limit::RequestPriority;
+use weav3r::points::{PointsPrice, PointsPriceCache};
+
+use crate::node::http::torn_api::{acquire_torn_api, report_torn_api_result, torn_api_http};
+
+/// Torn API 点数市场请求
+#[derive(GodotClass)]
+#[class(init, base=HttpRequest)]
+pub struct PointsMarketHttpRequest {
+    #[export]
+    type_name: GString,
+    #[base]
+    base: Base<HttpRequest>,
+    /// 正在请求的 key，用于记录限流结果
+    key: String,
+}
+
+impl PointsMarketHttpRequest {
+    /// 请求点数市场挂单，正在请求时忽略
+    pub fn send_request(&mut self, key: &str) -> bool {
+        if self.base().get_http_client_status() != godot::classes::http_client::Status::DISCONNECTED
+        {
+            godot_print!("PointsMarketHttpRequest: Already requesting.");
+            return false;
+        }
+
+        if key.is_empty() {
+            godot_warn!("PointsMarketHttpRequest: Key is empty.");
+            return false;
+        }
+
+        if !acquire_torn_api(key, "points_market", RequestPriority::Background) {
+            return false;
+        }
+
+        let http = torn_api_http(&PointsMarketReq, key);
+        if let Err(err) = http.send_request(&mut self.base_mut()) {
+            godot_error!("PointsMarketHttpRequest failed: {:?}", err);
+            return false;
+        }
+        self.key = key.to_string();
+        true
+    }
+
+    pub fn parse_response(
+        &self,
+        response_code: i64,
+        body: PackedByteArray,
+    ) -> Result<PointsPrice, MyError> {
+        let response_text = String::from_utf8_lossy(body.as_slice());
+        let result = PointsPriceCache::parse_price(
+            response_code,
+            &response_text,
+            tools::time::get_current_time(),
+        );
+        report_torn_api_result(&self.key, &result);
+        result.inspect_err(|e| {
+            godot_error!(
+                "PointsMarketHttpRequest: Failed to parse response: {:?},code: {},value: {}",
+                e,
+                response_code,
+                response_text
+            );
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use godot::prelude::*;
+use model::{
+    error::MyError,
+    torn::api::{TornApiRequest, parse_torn_response},
+};
+use tools::http::HttpTool;
+use torn_logic::rate_limit::{Acquire, RateLimiter, RequestPriority};
+
+/// 所有 Torn API 请求共用的限流器
+static TORN_API_LIMITER: OnceLock<Mutex<RateLimiter>> = OnceLock::new();
+
+pub fn torn_api_limiter() -> MutexGuard<'static, RateLimiter> {
+    TORN_API_LIMITER
+        .get_or_init(|| Mutex::new(RateLimiter::default()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// 申请发送一次 Torn API 请求，未获批准时保持排队，调用方稍后用同一个 ticket 重试
+pub fn acquire_torn_api(key: &str, ticket: &str, priority: RequestPriority) -> bool {
+    let now = tools::time::get_current_time();
+    match torn_api_limiter().acquire(key, ticket, priority, now) {
+        Acquire::Granted => true,
+        Acquire::Queued { position } => {
+            godot_print!("TornApi: {} queued, position: {}", ticket, position);
+            false
+        }
+        Acquire::BackingOff { until } => {
+            godot_print!("TornApi: {} backing off for {}s", ticket, until - now);
+            false
+        }
+    }
+}
+
+/// 取消排队，调用方不再重试时调用
+pub fn cancel_torn_api(key: &str, ticket: &str) {
+    torn_api_limiter().cancel(key, ticket);
+}
+
+/// 记录请求结果，"too many requests" 时暂停该 key 的请求
+pub fn report_torn_api_result<T>(key: &str, result: &Result<T, MyError>) {
+    match result {
+        Ok(_) => torn_api_limiter().on_success(key),
+        Err(MyError::TornTooManyRequests(_)) => {
+            godot_warn!("TornApi: Too many requests, backing off.");
+            torn_api_limiter().backoff(key, tools::time::get_current_time());
+        }
+        Err(_) => {}
+    }
+}
+
+/// 构建 Torn API v2 请求，key 通过 Authorization 头传入
+pub fn torn_api_http<R: TornApiRequest>(req: &R, key: &str) -> HttpTool {
+    let mut http = HttpTool::default();
+    http.set_url(&req.url());
+    http.add_header("accept", "application/json");
+    http.add_header("Authorization", format!("ApiKey {}", key).as_str());
+    http.set_method(godot::classes::http_client::Method::GET);
+    http
+}
+
+/// 解析 Torn API v2 响应，错误响应会转换为对应的错误类型
+pub fn parse_torn_api_response<R: TornApiRequest>(
+    key: &str,
+    response_code: i64,
+    body: &PackedByteArray,
+) -> Result<R::Response, MyError> {
+    let response_text = String::from_utf8_lossy(body.as_slice());
+    let result = parse_torn_response::<R::Response>(response_code, &response_text);
+    report_torn_api_result(key, &result);
+    result.inspect_err(|e| {
+        godot_error!(
+            "TornApi: Failed to parse response: {:?},value: {}",
+            e,
+            response_text
+        );
+    })
+}
@@ -0,0 +1,65 @@
+use godot::{classes::HttpRequest, prelude::*};
+use model::{error::MyError, items::ItemInfo, torn::items::TornItemsReq};
+use torn_logic::rate_limit::RequestPriority;
+
+use crate::node::http::torn_api::{
+    acquire_torn_api, cancel_torn_api, parse_torn_api_response, torn_api_http,
+};
+
+/// Torn API 物品列表请求，用于刷新物品目录
+#[derive(GodotClass)]
+#[class(init, base=HttpRequest)]
+pub struct TornItemsHttpRequest {
+    #[export]
+    type_name: GString,
+    #[base]
+    base: Base<HttpRequest>,
+    /// 正在请求的 key，用于记录限流结果
+    key: String,
+}
+
+impl TornItemsHttpRequest {
+    /// 请求全部物品，正在请求或限流排队中返回 false
+    pub fn send_request(&mut self, key: &str) -> bool {
+        if self.base().get_http_client_status() != godot::classes::http_client::Status::DISCONNECTED
+        {
+            godot_print!("TornItemsHttpRequest: Already requesting.");
+            return false;
+        }
+
+        if key.is_empty() {
+            godot_warn!("TornItemsHttpRequest: Key is empty.");
+            return false;
+        }
+
+        // 只在用户点击时请求，不会自动重试，排队时取消，避免阻塞其他请求
+        if !acquire_torn_api(key, "torn_items", RequestPriority::Interactive) {
+            cancel_torn_api(key, "torn_items");
+            return false;
+        }
+
+        let http = torn_api_http(&TornItemsReq::default(), key);
+        if let Err(err) = http.send_request(&mut self.base_mut()) {
+            godot_error!("TornItemsHttpRequest failed: {:?}", err);
+            return false;
+        }
+        self.key = key.to_string();
+        true
+    }
+
+    pub fn parse_response(
+        &self,
+        response_code: i64,
+        body: PackedByteArray,
+    ) -> Result<Vec<ItemInfo>, MyError> {
+        let items = parse_torn_api_response::<TornItemsReq>(&self.key, response_code, &body)?
+            .items
+            .iter()
+            .map(|x| x.to_item_info())
+            .collect::<Vec<ItemInfo>>();
+        if items.is_empty() {
+            return Err(MyError::JsonParse("item list is empty".to_string()));
+        }
+        Ok(items)
+    }
+}
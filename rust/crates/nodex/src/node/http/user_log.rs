@@ -1,9 +1,26 @@
 use godot::{classes::HttpRequest, prelude::*};
 use model::{
     error::MyError,
-    user_log::{UserLogEntry, UserLogReq, UserLogResponse},
+    user_log::{UserLogEntry, UserLogReq},
 };
-use tools::http::HttpTool;
+use torn_logic::rate_limit::RequestPriority;
+
+use crate::node::http::torn_api::{
+    acquire_torn_api, cancel_torn_api, parse_torn_api_response, torn_api_http,
+};
+
+/// 限流排队使用的 ticket
+const USER_LOG_TICKET: &str = "user_log";
+
+/// 发送请求的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendStatus {
+    Sent,
+    /// 限流排队中，稍后重试
+    Queued,
+    /// 正在请求、key 为空或者发送失败，重试没有意义
+    Failed,
+}
 
 #[derive(GodotClass)]
 #[class(init, base=HttpRequest)]
@@ -12,66 +29,54 @@ pub struct UserLogHttpRequest {
     type_name: GString,
     #[base]
     base: Base<HttpRequest>,
+    /// 正在请求的 key，用于记录限流结果
+    key: String,
 }
 
 impl UserLogHttpRequest {
-    pub fn send_request(&mut self, req: UserLogReq) {
+    /// 发送请求
+    pub fn send_request(
+        &mut self,
+        req: UserLogReq,
+        key: &str,
+        priority: RequestPriority,
+    ) -> SendStatus {
         if self.base().get_http_client_status() != godot::classes::http_client::Status::DISCONNECTED
         {
             godot_print!("Weav3rHttpRequest: Already requesting.");
-            return;
+            return SendStatus::Failed;
         }
 
-        if req.key.is_empty() {
+        if key.is_empty() {
             godot_error!("UserLogHttpRequest: Key is empty.");
-            return;
+            return SendStatus::Failed;
         }
 
-        let mut http = HttpTool::default();
-        http.set_url("https://api.torn.com/v2/user/log");
-        http.add_param("limit", req.limit.to_string().as_str());
-        http.add_param("key", req.key.as_str());
-        if let Some(target) = req.target {
-            http.add_param("target", target.to_string().as_str());
-        }
-        if let Some(cat) = req.cat {
-            http.add_param("cat", cat.to_string().as_str());
+        if !acquire_torn_api(key, USER_LOG_TICKET, priority) {
+            return SendStatus::Queued;
         }
-        http.add_header("accept", "application/json");
-        http.set_method(godot::classes::http_client::Method::GET);
+
+        let http = torn_api_http(&req, key);
         if let Err(err) = http.send_request(&mut self.base_mut()) {
             godot_error!("UserLogHttpRequest failed: {:?}", err);
+            return SendStatus::Failed;
         }
+        self.key = key.to_string();
+        SendStatus::Sent
+    }
+
+    /// 放弃排队中的请求
+    pub fn cancel_queued(&self, key: &str) {
+        cancel_torn_api(key, USER_LOG_TICKET);
     }
 
     pub fn parse_response(
+        &self,
         _result: i64,
         response_code: i64,
         _headers: PackedStringArray,
         body: PackedByteArray,
     ) -> Result<Vec<UserLogEntry>, MyError> {
-        if response_code != 200 {
-            godot_error!(
-                "UserLogHttpRequest: Failed to get response.code: {}",
-                response_code
-            );
-            return Err(MyError::NetworkCode(
-                response_code,
-                String::from("Failed to get response."),
-            ));
-        }
-
-        let response_text = String::from_utf8_lossy(body.as_slice());
-        match UserLogResponse::from_json(&response_text) {
-            Ok(resp) => Ok(resp.log),
-            Err(e) => {
-                godot_error!(
-                    "UserLogHttpRequest: Failed to parse response: {:?},value: {}",
-                    e,
-                    response_text
-                );
-                Err(e)
-            }
-        }
+        parse_torn_api_response::<UserLogReq>(&self.key, response_code, &body).map(|resp| resp.log)
     }
 }
@@ -1,6 +1,6 @@
 use godot::{classes::HttpRequest, prelude::*};
 use std::time::Instant;
-use tools::http::HttpTool;
+use tools::http::{CookieJar, HttpTool};
 
 #[derive(GodotClass)]
 #[class(init, base=HttpRequest)]
@@ -23,8 +23,8 @@ impl Weav3rHttpRequest {
         }
     }
 
-    #[func]
-    pub fn send_request(&mut self, target_ids: GString, next_action: String, cookie: String) {
+    /// cookies 直接传给 HttpTool，保留过期时间
+    pub fn send_request(&mut self, target_ids: GString, next_action: String, cookies: CookieJar) {
         if self.base().get_http_client_status() != godot::classes::http_client::Status::DISCONNECTED
         {
             godot_print!("Weav3rHttpRequest: Already requesting.");
@@ -39,7 +39,7 @@ impl Weav3rHttpRequest {
             godot_warn!("Weav3rHttpRequest: next_action is empty.");
             return;
         }
-        if cookie.is_empty() {
+        if cookies.is_empty() {
             godot_warn!("Weav3rHttpRequest: cookie is empty.");
             return;
         }
@@ -56,7 +56,7 @@ impl Weav3rHttpRequest {
         http.add_header("Accept-Language", "zh-CN,zh;q=0.8");
         http.add_header("Content-Type", "text/plain;charset=UTF-8");
         http.add_header("Next-Action", next_action.as_str());
-        http.add_header("Cookie", cookie.as_str());
+        http.set_cookies(cookies);
         http.set_body(format!("[[{}]]", target_ids).as_bytes().to_vec());
         let request_result = http.send_request(&mut self.base_mut());
         if let Err(err) = request_result {
@@ -2,4 +2,5 @@ pub mod prelude;
 
 pub mod http;
 pub mod profit_panel;
+pub mod purchase_plan_panel;
 pub mod weav3r_item;
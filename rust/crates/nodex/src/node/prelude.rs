@@ -1,3 +1,6 @@
-pub use crate::node::http::{weav3r_favorites::*, user_log::*};
+pub use crate::node::http::{
+    item_market::*, points_market::*, torn_items::*, user_log::*, weav3r_favorites::*,
+};
 pub use crate::node::profit_panel::*;
+pub use crate::node::purchase_plan_panel::*;
 pub use crate::node::weav3r_item::*;
\ No newline at end of file
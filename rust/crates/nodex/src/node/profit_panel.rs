@@ -1,5 +1,6 @@
 use godot::{classes::*, prelude::*};
 use tools::node::{INodeFunc, INodeTool};
+use torn_logic::item::EquipmentKind;
 use weav3r::profit::{ProfitInfo, ProfitMetrics};
 
 use crate::node::http::image::ImageHttpRequest;
@@ -12,6 +13,10 @@ pub struct ProfitPanel {
     base: Base<PanelContainer>,
     name_label: Option<Gd<Label>>,
     quantity_label: Option<Gd<Label>>,
+    confidence_label: Option<Gd<Label>>,
+    item_market_label: Option<Gd<Label>>,
+    museum_label: Option<Gd<Label>>,
+    equipment_label: Option<Gd<Label>>,
     image_request: Option<Gd<ImageHttpRequest>>,
     office_icon: Option<Gd<TextureRect>>,
     vbox_profit_list: Option<Gd<VBoxContainer>>,
@@ -22,6 +27,10 @@ impl IPanelContainer for ProfitPanel {
     fn ready(&mut self) {
         self.name_label = self.get_node_as::<Label>("%Name");
         self.quantity_label = self.get_node_as::<Label>("%Quantity");
+        self.confidence_label = self.get_node_as::<Label>("%Confidence");
+        self.item_market_label = self.get_node_as::<Label>("%ItemMarket");
+        self.museum_label = self.get_node_as::<Label>("%Museum");
+        self.equipment_label = self.get_node_as::<Label>("%Equipment");
         self.image_request = self.get_node_as::<ImageHttpRequest>("%ImageHttpRequest");
         self.office_icon = self.get_node_as::<TextureRect>("%OfficeIcon");
         self.vbox_profit_list = self.get_node_as::<VBoxContainer>("%VBoxProfitList");
@@ -33,6 +42,38 @@ impl IPanelContainer for ProfitPanel {
         if let Some(quantity_label) = self.quantity_label.as_mut() {
             quantity_label.set_text(format!("Quantity:{}", self.item.quantity).as_str());
         }
+        if let Some(confidence_label) = self.confidence_label.as_mut() {
+            let confidence = &self.item.confidence;
+            confidence_label.set_text(format!("Confidence:{:.0}", confidence.score).as_str());
+            // 鼠标悬停显示扣分原因
+            confidence_label.set_tooltip_text(confidence.describe().as_str());
+        }
+        if let Some(item_market_label) = self.item_market_label.as_mut() {
+            match self.item.item_market_ask {
+                Some(ask) => item_market_label.set_text(format!("Market Ask:{}", ask).as_str()),
+                None => item_market_label.set_visible(false),
+            }
+        }
+        if let Some(museum_label) = self.museum_label.as_mut() {
+            match &self.item.museum_profit {
+                Some(museum) => {
+                    // 最终利润使用博物馆渠道时标记出来
+                    let mark = if self.item.final_profit.is_museum { "*" } else { "" };
+                    museum_label.set_text(
+                        format!("Museum{}:{}", mark, museum.single_profit_value).as_str(),
+                    );
+                }
+                None => museum_label.set_visible(false),
+            }
+        }
+        if let Some(equipment_label) = self.equipment_label.as_mut() {
+            // 装备只按基础价值计算，实际价值取决于品质和加成
+            match self.item.equipment {
+                Some(EquipmentKind::Weapon) => equipment_label.set_text("Weapon: base value only"),
+                Some(EquipmentKind::Armor) => equipment_label.set_text("Armor: base value only"),
+                None => equipment_label.set_visible(false),
+            }
+        }
         if !self.item.final_profit.is_office
             && let Some(office_icon) = self.office_icon.as_mut()
         {
@@ -104,8 +145,13 @@ impl IControl for ProfitItem {
                 .set_text(format!("Single Profit:{}", self.profit.single_profit_value).as_str());
         }
         if let Some(profit_total_label) = self.profit_total_label.as_mut() {
-            profit_total_label
-                .set_text(format!("Total Profit:{}", self.profit.total_profit_value).as_str());
+            profit_total_label.set_text(
+                format!(
+                    "Total Profit:{} (Gross:{})",
+                    self.profit.total_profit_value, self.profit.total_gross_profit_value
+                )
+                .as_str(),
+            );
         }
         if let Some(profit_percent_label) = self.profit_percent_label.as_mut() {
             profit_percent_label
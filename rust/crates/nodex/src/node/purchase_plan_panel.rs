@@ -0,0 +1,164 @@
+use godot::{classes::*, prelude::*};
+use tools::{
+    cfg::CfgTool,
+    node::{INodeFunc, INodeTool},
+};
+use weav3r::{
+    data::Weav3rSettingData,
+    planner::{PlanParams, plan_purchases},
+    profit::{ProfitInfo, get_bazaar_url},
+};
+
+/// 购买计划面板，根据可用资金生成购买清单
+#[derive(GodotClass)]
+#[class(init,base=PanelContainer)]
+pub struct PurchasePlanPanel {
+    #[base]
+    base: Base<PanelContainer>,
+    budget_edit: Option<Gd<SpinBox>>,
+    quantity_caps_edit: Option<Gd<LineEdit>>,
+    summary_label: Option<Gd<Label>>,
+    plan_vbox: Option<Gd<VBoxContainer>>,
+    params: PlanParams,
+    /// 未合并的单个挂单
+    listings: Vec<ProfitInfo>,
+}
+
+#[godot_api]
+impl IPanelContainer for PurchasePlanPanel {
+    fn ready(&mut self) {
+        self.budget_edit = self.get_node_as::<SpinBox>("%BudgetEdit");
+        self.quantity_caps_edit = self.get_node_as::<LineEdit>("%QuantityCapsEdit");
+        self.summary_label = self.get_node_as::<Label>("%PlanSummary");
+        self.plan_vbox = self.get_node_as::<VBoxContainer>("%PlanVBox");
+
+        match CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(cfg) => {
+                let setting_data = Weav3rSettingData::new(cfg);
+                self.params.budget = setting_data.get_plan_budget().max(0) as u64;
+                self.params.quantity_caps = setting_data.get_plan_quantity_caps();
+            }
+            Err(err) => {
+                godot_error!(
+                    "PurchasePlanPanel: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+            }
+        };
+
+        if let Some(budget_edit) = self.budget_edit.as_mut() {
+            budget_edit.set_value(self.params.budget as f64);
+            budget_edit
+                .clone()
+                .signals()
+                .value_changed()
+                .connect_other(self, Self::on_budget_changed);
+        }
+        if let Some(quantity_caps_edit) = self.quantity_caps_edit.as_mut() {
+            quantity_caps_edit.set_text(
+                PlanParams::format_quantity_caps(&self.params.quantity_caps).as_str(),
+            );
+            quantity_caps_edit
+                .clone()
+                .signals()
+                .text_submitted()
+                .connect_other(self, Self::on_quantity_caps_submitted);
+        }
+        self.render();
+    }
+}
+
+impl INodeFunc for PurchasePlanPanel {
+    fn node_path() -> &'static str {
+        "res://scenes/purchase_plan_panel.tscn"
+    }
+}
+
+#[godot_api]
+impl PurchasePlanPanel {
+    /// 更新挂单利润数据，重新生成购买清单
+    pub fn set_items(&mut self, listings: Vec<ProfitInfo>) {
+        self.listings = listings;
+        self.render();
+    }
+
+    #[func]
+    fn on_budget_changed(&mut self, value: f64) {
+        self.params.budget = value.round().max(0.0) as u64;
+        self.save_params();
+        self.render();
+    }
+
+    #[func]
+    fn on_quantity_caps_submitted(&mut self, text: GString) {
+        self.params.quantity_caps = PlanParams::parse_quantity_caps(&text.to_string());
+        self.save_params();
+        self.render();
+    }
+
+    fn save_params(&mut self) {
+        let cfg = match CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "PurchasePlanPanel: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+                return;
+            }
+        };
+        let mut setting_data = Weav3rSettingData::new(cfg);
+        setting_data.set_plan_budget(self.params.budget as i64);
+        setting_data.set_plan_quantity_caps(&self.params.quantity_caps);
+        if let Err(err) = setting_data.save() {
+            godot_error!(
+                "PurchasePlanPanel: Failed to save {:?}: {:?}",
+                Weav3rSettingData::SETTINGS_PATH,
+                err
+            );
+        }
+    }
+
+    fn render(&mut self) {
+        let plan = plan_purchases(&self.params, &self.listings);
+
+        if let Some(summary_label) = self.summary_label.as_mut() {
+            summary_label.set_text(
+                format!(
+                    "Cost:{}/{} Profit:{}",
+                    plan.total_cost, plan.budget, plan.total_profit
+                )
+                .as_str(),
+            );
+        }
+
+        let Some(vbox) = self.plan_vbox.as_mut() else {
+            return;
+        };
+        for child in vbox.get_children().iter_shared() {
+            let mut child = child.clone();
+            child.queue_free();
+        }
+
+        for (i, entry) in plan.entries.iter().enumerate() {
+            let mut link = LinkButton::new_alloc();
+            link.set_uri(get_bazaar_url(entry.player_id).as_str());
+            link.set_text(
+                format!(
+                    "{}. {} {} x{} @{} | Cash:{} Profit:{}",
+                    i + 1,
+                    entry.player_name,
+                    entry.name,
+                    entry.quantity,
+                    entry.single_price,
+                    entry.cash_used,
+                    entry.profit_sum
+                )
+                .as_str(),
+            );
+            vbox.add_child(&link.upcast::<Node>());
+        }
+    }
+}
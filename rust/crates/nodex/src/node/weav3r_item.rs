@@ -1,6 +1,13 @@
 use godot::{classes::*, prelude::*};
-use tools::node::{INodeFunc, INodeTool};
-use weav3r::profit::{ProfitUserInfo, get_bazaar_url};
+use tools::{
+    cfg::CfgTool,
+    node::{INodeFunc, INodeTool},
+};
+use weav3r::{
+    data::Weav3rSettingData,
+    player::PlayerMark,
+    profit::{ProfitUserInfo, get_bazaar_url},
+};
 
 use crate::node::prelude::*;
 
@@ -13,6 +20,9 @@ pub struct Weav3rItem {
     base: Base<PanelContainer>,
     user_name: Option<Gd<Label>>,
     total_profit: Option<Gd<Label>>,
+    note_label: Option<Gd<Label>>,
+    favorite_button: Option<Gd<Button>>,
+    block_button: Option<Gd<Button>>,
     link_button: Option<Gd<LinkButton>>,
     profit_vbox: Option<Gd<VBoxContainer>>,
     top_bar: Option<Gd<PanelContainer>>,
@@ -26,9 +36,27 @@ impl IPanelContainer for Weav3rItem {
     fn ready(&mut self) {
         self.user_name = self.get_node_as::<Label>("%UserName");
         self.total_profit = self.get_node_as::<Label>("%TotalProfit");
+        self.note_label = self.get_node_as::<Label>("%Note");
+        self.favorite_button = self.get_node_as::<Button>("%FavoriteButton");
+        self.block_button = self.get_node_as::<Button>("%BlockButton");
         self.link_button = self.get_node_as::<LinkButton>("%LinkButton");
         self.profit_vbox = self.get_node_as::<VBoxContainer>("%ProfitVBox");
         self.top_bar = self.get_node_as::<PanelContainer>("VBoxContainer/UserInfo");
+
+        if let Some(favorite_button) = self.favorite_button.as_ref() {
+            favorite_button
+                .clone()
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_favorite_pressed);
+        }
+        if let Some(block_button) = self.block_button.as_ref() {
+            block_button
+                .clone()
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_block_pressed);
+        }
         self.update_ui();
     }
 
@@ -67,11 +95,18 @@ impl Weav3rItem {
 
     fn update_ui(&mut self) {
         if let Some(user_name) = self.user_name.as_mut() {
-            user_name.set_text(format!("Name:{}", self.item.player_name).as_str());
+            let pin = if self.item.is_pinned { "📌" } else { "" };
+            user_name.set_text(format!("{}Name:{}", pin, self.item.player_name).as_str());
         }
         if let Some(total_profit) = self.total_profit.as_mut() {
             total_profit.set_text(format!("Profit:{}", self.item.total_profit_price).as_str());
         }
+        if let Some(note_label) = self.note_label.as_mut() {
+            note_label.set_visible(!self.item.note.is_empty());
+            note_label.set_text(format!("Note:{}", self.item.note).as_str());
+            note_label.set_tooltip_text(self.item.note.as_str());
+        }
+        self.update_favorite_button();
         if let Some(link_button) = self.link_button.as_mut() {
             link_button.set_uri(get_bazaar_url(self.item.player_id).as_str());
             link_button.set_text("Link");
@@ -109,6 +144,61 @@ impl Weav3rItem {
         }
     }
 
+    fn update_favorite_button(&mut self) {
+        if let Some(favorite_button) = self.favorite_button.as_mut() {
+            favorite_button.set_text(if self.item.is_favorite { "★" } else { "☆" });
+        }
+    }
+
+    #[func]
+    fn on_favorite_pressed(&mut self) {
+        let mark = if self.item.is_favorite {
+            PlayerMark::None
+        } else {
+            PlayerMark::Favorite
+        };
+        if self.save_player_mark(mark) {
+            self.item.is_favorite = mark == PlayerMark::Favorite;
+            self.update_favorite_button();
+        }
+    }
+
+    /// 屏蔽后直接隐藏，下次刷新时会被过滤掉
+    #[func]
+    fn on_block_pressed(&mut self) {
+        if self.save_player_mark(PlayerMark::Blocked) {
+            self.base_mut().set_visible(false);
+        }
+    }
+
+    /// 保存玩家标记，保留原有备注
+    fn save_player_mark(&mut self, mark: PlayerMark) -> bool {
+        let cfg = match CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "Weav3rItem: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+                return false;
+            }
+        };
+        let mut setting_data = Weav3rSettingData::new(cfg);
+        let mut players = setting_data.get_players();
+        players.set_mark(self.item.player_id, mark);
+        setting_data.set_players(&players);
+        if let Err(err) = setting_data.save() {
+            godot_error!(
+                "Weav3rItem: Failed to save {:?}: {:?}",
+                Weav3rSettingData::SETTINGS_PATH,
+                err
+            );
+            return false;
+        }
+        true
+    }
+
     fn set_color(&mut self, sec: u64) {
         if sec > self.light_sec {
             return;
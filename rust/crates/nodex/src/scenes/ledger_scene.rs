@@ -0,0 +1,328 @@
+use godot::{classes::*, prelude::*};
+use tools::node::{INodeFunc, INodeTool};
+use torn_logic::{
+    ledger::Ledger,
+    log_store::{SyncKind, SyncPage, UserLogStore},
+    rate_limit::RequestPriority,
+};
+use weav3r::data::Weav3rSettingData;
+
+use crate::node::http::{
+    torn_api::torn_api_limiter,
+    user_log::{SendStatus, UserLogHttpRequest},
+};
+
+/// 每次拉取的日志数量
+const LOG_LIMIT: u32 = 100;
+/// 限流排队时的重试间隔
+const RETRY_SEC: f64 = 1.0;
+/// 后台同步检查间隔
+const SYNC_TICK_SEC: f64 = 10.0;
+/// 后台拉取新日志的间隔
+const FORWARD_INTERVAL_SEC: u64 = 300;
+
+/// 已实现盈亏页面，从用户日志重建买卖记录
+#[derive(GodotClass)]
+#[class(init,base=Control)]
+pub struct LedgerScene {
+    #[base]
+    base: Base<Control>,
+    refresh_button: Option<Gd<Button>>,
+    summary_label: Option<Gd<Label>>,
+    item_label: Option<Gd<Label>>,
+    day_label: Option<Gd<Label>>,
+    seller_label: Option<Gd<Label>>,
+    user_log_request: Option<Gd<UserLogHttpRequest>>,
+    /// 限流排队时的重试定时器，同一时间只有一个重试
+    retry_timer: Option<Gd<Timer>>,
+    /// 开始排队的时间，超过排队超时后放弃
+    retry_since: Option<u64>,
+    /// 最近一次请求使用的 key
+    api_key: String,
+    /// 正在请求的日志范围
+    sync_page: Option<SyncPage>,
+    last_forward_at: u64,
+    store: UserLogStore,
+    ledger: Ledger,
+}
+
+#[godot_api]
+impl IControl for LedgerScene {
+    fn ready(&mut self) {
+        self.refresh_button = self.get_node_as::<Button>("%LedgerRefresh");
+        self.summary_label = self.get_node_as::<Label>("%LedgerSummary");
+        self.item_label = self.get_node_as::<Label>("%LedgerByItem");
+        self.day_label = self.get_node_as::<Label>("%LedgerByDay");
+        self.seller_label = self.get_node_as::<Label>("%LedgerBySeller");
+
+        if let Some(refresh_button) = self.refresh_button.as_ref() {
+            refresh_button
+                .clone()
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_refresh_pressed);
+        }
+        let user_log_request = UserLogHttpRequest::new_alloc();
+        self.base_mut().add_child(&user_log_request);
+        user_log_request
+            .signals()
+            .request_completed()
+            .connect_other(self, Self::on_user_log_completed);
+        self.user_log_request = Some(user_log_request);
+
+        let mut retry_timer = Timer::new_alloc();
+        retry_timer.set_wait_time(RETRY_SEC);
+        retry_timer.set_one_shot(true);
+        self.base_mut().add_child(&retry_timer);
+        retry_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::on_retry_timeout);
+        self.retry_timer = Some(retry_timer);
+
+        let mut sync_timer = Timer::new_alloc();
+        sync_timer.set_wait_time(SYNC_TICK_SEC);
+        self.base_mut().add_child(&sync_timer);
+        sync_timer
+            .signals()
+            .timeout()
+            .connect_other(self, Self::on_sync_timeout);
+        sync_timer.start();
+
+        self.load_store();
+        self.ledger = Ledger::from_logs(&self.store.entries());
+        self.render();
+    }
+}
+
+impl INodeFunc for LedgerScene {
+    fn node_path() -> &'static str {
+        "res://scenes/ledger.tscn"
+    }
+}
+
+#[godot_api]
+impl LedgerScene {
+    /// 手动刷新，拉取新日志，已经在排队重试时不重复发送
+    #[func]
+    fn on_refresh_pressed(&mut self) {
+        if self.retry_since.is_some() {
+            return;
+        }
+        self.retry_since = Some(tools::time::get_current_time());
+        self.refresh();
+    }
+
+    #[func]
+    fn on_retry_timeout(&mut self) {
+        if self.retry_since.is_some() {
+            self.refresh();
+        }
+    }
+
+    /// 发送手动刷新的请求，限流排队中稍后重试，超过排队超时后放弃
+    fn refresh(&mut self) {
+        let page = self.store.forward_page();
+        match self.send_sync(page, RequestPriority::Interactive) {
+            Some(SendStatus::Queued) => {}
+            Some(SendStatus::Failed) => {
+                godot_warn!("LedgerScene: Failed to refresh user log.");
+                self.retry_since = None;
+                return;
+            }
+            _ => {
+                self.retry_since = None;
+                return;
+            }
+        }
+        let now = tools::time::get_current_time();
+        let timeout = torn_api_limiter().queue_timeout_sec;
+        if self
+            .retry_since
+            .is_none_or(|since| now.saturating_sub(since) >= timeout)
+        {
+            godot_warn!("LedgerScene: User log request queued too long, giving up.");
+            if let Some(request) = self.user_log_request.as_ref() {
+                request.bind().cancel_queued(&self.api_key);
+            }
+            self.retry_since = None;
+            return;
+        }
+        if let Some(timer) = self.retry_timer.as_mut() {
+            timer.start();
+        }
+    }
+
+    /// 后台同步：定时拉取新日志，其余时间逐页补齐历史日志
+    #[func]
+    fn on_sync_timeout(&mut self) {
+        if self.sync_page.is_some() {
+            return;
+        }
+        let now = tools::time::get_current_time();
+        let forward = self.store.forward_page();
+        let page = if forward.to.is_some()
+            || now.saturating_sub(self.last_forward_at) >= FORWARD_INTERVAL_SEC
+        {
+            forward
+        } else if let Some(page) = self.store.backfill_page() {
+            page
+        } else {
+            return;
+        };
+        self.send_sync(page, RequestPriority::Background);
+    }
+
+    /// 发送一页日志请求，正在同步或者没有 key 时返回 None
+    fn send_sync(&mut self, page: SyncPage, priority: RequestPriority) -> Option<SendStatus> {
+        if self.sync_page.is_some() {
+            return None;
+        }
+        let cfg = match tools::cfg::CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "LedgerScene: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+                return None;
+            }
+        };
+        let setting_data = Weav3rSettingData::new(cfg);
+        let key = setting_data.get_api_key();
+        if key.is_empty() {
+            return None;
+        }
+        torn_api_limiter().budget_per_minute = setting_data.get_torn_api_budget().max(1) as u32;
+        let request = self.user_log_request.as_mut()?;
+        let status = request
+            .bind_mut()
+            .send_request(page.to_req(LOG_LIMIT), &key, priority);
+        self.api_key = key;
+        if status != SendStatus::Sent {
+            return Some(status);
+        }
+        if page.kind == SyncKind::Forward {
+            self.last_forward_at = tools::time::get_current_time();
+        }
+        self.sync_page = Some(page);
+        Some(status)
+    }
+
+    #[func]
+    fn on_user_log_completed(
+        &mut self,
+        result: i64,
+        response_code: i64,
+        headers: PackedStringArray,
+        body: PackedByteArray,
+    ) {
+        let Some(page) = self.sync_page.take() else {
+            return;
+        };
+        let Some(request) = self.user_log_request.as_ref() else {
+            return;
+        };
+        let Ok(logs) = request
+            .bind()
+            .parse_response(result, response_code, headers, body)
+        else {
+            return;
+        };
+        let added = self.store.apply_page(page, logs, LOG_LIMIT as usize);
+        self.save_store();
+        if added > 0 {
+            self.ledger = Ledger::from_logs(&self.store.entries());
+            self.render();
+        }
+    }
+
+    fn load_store(&mut self) {
+        let Some(json) = tools::file::read_text(UserLogStore::STORE_PATH) else {
+            return;
+        };
+        match UserLogStore::from_json(&json) {
+            Ok(store) => self.store = store,
+            Err(err) => godot_error!(
+                "LedgerScene: Failed to parse {:?}: {:?}",
+                UserLogStore::STORE_PATH,
+                err
+            ),
+        }
+    }
+
+    fn save_store(&self) {
+        let json = match self.store.to_json() {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!("LedgerScene: Failed to serialize user log: {:?}", err);
+                return;
+            }
+        };
+        if let Err(err) = tools::file::write_text(UserLogStore::STORE_PATH, &json) {
+            godot_error!(
+                "LedgerScene: Failed to save {:?}: {:?}",
+                UserLogStore::STORE_PATH,
+                err
+            );
+        }
+    }
+
+    fn render(&mut self) {
+        let item_map = torn_logic::item::get_item_info_map();
+        let item_name = |id: &i32| {
+            item_map
+                .get(id)
+                .map(|x| x.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let summary = format!(
+            "Realized:{} Sales:{} Open Lots:{} Unmatched:{} Logs:{}",
+            self.ledger.total_profit(),
+            self.ledger.realized().len(),
+            self.ledger.open_lots().count(),
+            self.ledger.unmatched().len(),
+            self.store.len()
+        );
+        let by_item = self
+            .ledger
+            .profit_by_item()
+            .iter()
+            .map(|(id, profit)| format!("{}: {}", item_name(id), profit))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let time = Time::singleton();
+        let by_day = self
+            .ledger
+            .profit_by_day()
+            .iter()
+            .rev()
+            .map(|(day, profit)| {
+                format!("{}: {}", time.get_date_string_from_unix_time(*day), profit)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let by_seller = self
+            .ledger
+            .profit_by_seller()
+            .iter()
+            .map(|(seller, profit)| format!("{}: {}", seller, profit))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if let Some(label) = self.summary_label.as_mut() {
+            label.set_text(summary.as_str());
+        }
+        if let Some(label) = self.item_label.as_mut() {
+            label.set_text(by_item.as_str());
+        }
+        if let Some(label) = self.day_label.as_mut() {
+            label.set_text(by_day.as_str());
+        }
+        if let Some(label) = self.seller_label.as_mut() {
+            label.set_text(by_seller.as_str());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use godot::{classes::*, prelude::*};
+use tools::{
+    node::{INodeFunc, INodeTool},
+    time::format_duration,
+};
+use weav3r::lifecycle::{LifetimeStats, ListingTracker};
+
+/// 挂单生命周期统计页面
+#[derive(GodotClass)]
+#[class(init,base=Control)]
+pub struct ListingStatsScene {
+    #[base]
+    base: Base<Control>,
+    band_width_edit: Option<Gd<SpinBox>>,
+    summary_label: Option<Gd<Label>>,
+    item_stats_label: Option<Gd<Label>>,
+    band_stats_label: Option<Gd<Label>>,
+    tracker: ListingTracker,
+}
+
+#[godot_api]
+impl IControl for ListingStatsScene {
+    fn ready(&mut self) {
+        self.band_width_edit = self.get_node_as::<SpinBox>("%BandWidthEdit");
+        self.summary_label = self.get_node_as::<Label>("%StatsSummary");
+        self.item_stats_label = self.get_node_as::<Label>("%ItemStats");
+        self.band_stats_label = self.get_node_as::<Label>("%BandStats");
+
+        if let Some(band_width_edit) = self.band_width_edit.as_ref() {
+            band_width_edit
+                .clone()
+                .signals()
+                .value_changed()
+                .connect_other(self, Self::on_band_width_changed);
+        }
+        self.render();
+    }
+}
+
+impl INodeFunc for ListingStatsScene {
+    fn node_path() -> &'static str {
+        "res://scenes/listing_stats.tscn"
+    }
+}
+
+#[godot_api]
+impl ListingStatsScene {
+    /// 更新挂单数据并刷新统计
+    pub fn set_tracker(&mut self, tracker: ListingTracker) {
+        self.tracker = tracker;
+        self.render();
+    }
+
+    #[func]
+    fn on_band_width_changed(&mut self, _value: f64) {
+        self.render();
+    }
+
+    fn render(&mut self) {
+        let band_width = self
+            .band_width_edit
+            .as_ref()
+            .map(|x| x.get_value().round() as u32)
+            .unwrap_or(5);
+
+        if let Some(summary_label) = self.summary_label.as_mut() {
+            summary_label.set_text(
+                format!(
+                    "Active:{} Closed:{}",
+                    self.tracker.active().count(),
+                    self.tracker.closed().count()
+                )
+                .as_str(),
+            );
+        }
+
+        if let Some(item_stats_label) = self.item_stats_label.as_mut() {
+            let text = self
+                .tracker
+                .item_stats()
+                .iter()
+                .map(|x| format!("[{}] {} | {}", x.id, x.name, format_stats(&x.stats)))
+                .collect::<Vec<String>>()
+                .join("\n");
+            item_stats_label.set_text(text.as_str());
+        }
+
+        if let Some(band_stats_label) = self.band_stats_label.as_mut() {
+            let text = self
+                .tracker
+                .discount_band_stats(band_width)
+                .iter()
+                .map(|x| {
+                    format!(
+                        "{}% ~ {}% | {}",
+                        x.discount_from,
+                        x.discount_from + band_width as i32,
+                        format_stats(&x.stats)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            band_stats_label.set_text(text.as_str());
+        }
+    }
+}
+
+fn format_stats(stats: &LifetimeStats) -> String {
+    format!(
+        "Count:{} Median:{} Min:{} Price Changes:{}",
+        stats.count,
+        format_duration(stats.median_lifetime),
+        format_duration(stats.min_lifetime),
+        stats.price_changes
+    )
+}
@@ -1,5 +1,8 @@
 use crate::{
-    scenes::{weav3r_scene::Weav3rScene, weav3r_setting_scene::Weav3rSettingScene},
+    scenes::{
+        ledger_scene::LedgerScene, listing_stats_scene::ListingStatsScene,
+        weav3r_scene::Weav3rScene, weav3r_setting_scene::Weav3rSettingScene,
+    },
 };
 use godot::{classes::*, prelude::*};
 use tools::node::{INodeFunc, INodeTool};
@@ -11,8 +14,12 @@ pub struct MainScene {
     base: Base<Node>,
     weav3r_page: Option<Gd<Weav3rScene>>,
     settings_page: Option<Gd<Weav3rSettingScene>>,
+    stats_page: Option<Gd<ListingStatsScene>>,
+    ledger_page: Option<Gd<LedgerScene>>,
     home_button: Option<Gd<Button>>,
     settings_button: Option<Gd<Button>>,
+    stats_button: Option<Gd<Button>>,
+    ledger_button: Option<Gd<Button>>,
 }
 
 #[godot_api]
@@ -20,8 +27,12 @@ impl INode for MainScene {
     fn ready(&mut self) {
         self.weav3r_page = self.get_node_as::<Weav3rScene>("%Weav3rPage");
         self.settings_page = self.get_node_as::<Weav3rSettingScene>("%Weav3rSettingPage");
+        self.stats_page = self.get_node_as::<ListingStatsScene>("%ListingStatsPage");
+        self.ledger_page = self.get_node_as::<LedgerScene>("%LedgerPage");
         self.home_button = self.get_node_as::<Button>("%Home");
         self.settings_button = self.get_node_as::<Button>("%Setting");
+        self.stats_button = self.get_node_as::<Button>("%Stats");
+        self.ledger_button = self.get_node_as::<Button>("%Ledger");
         if let Some(home_button) = self.home_button.as_mut() {
             home_button
                 .clone()
@@ -36,6 +47,27 @@ impl INode for MainScene {
                 .pressed()
                 .connect_other(self, Self::on_settings_button_pressed);
         }
+        if let Some(stats_button) = self.stats_button.as_mut() {
+            stats_button
+                .clone()
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_stats_button_pressed);
+        }
+        if let Some(weav3r_page) = self.weav3r_page.as_ref() {
+            weav3r_page
+                .clone()
+                .signals()
+                .open_settings()
+                .connect_other(self, Self::on_settings_button_pressed);
+        }
+        if let Some(ledger_button) = self.ledger_button.as_mut() {
+            ledger_button
+                .clone()
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_ledger_button_pressed);
+        }
     }
 }
 
@@ -53,6 +85,12 @@ impl MainScene {
         if let Some(settings_page) = self.settings_page.as_mut() {
             settings_page.hide();
         }
+        if let Some(stats_page) = self.stats_page.as_mut() {
+            stats_page.hide();
+        }
+        if let Some(ledger_page) = self.ledger_page.as_mut() {
+            ledger_page.hide();
+        }
     }
 
     fn on_settings_button_pressed(&mut self) {
@@ -62,5 +100,49 @@ impl MainScene {
         if let Some(settings_page) = self.settings_page.as_mut() {
             settings_page.show();
         }
+        if let Some(stats_page) = self.stats_page.as_mut() {
+            stats_page.hide();
+        }
+        if let Some(ledger_page) = self.ledger_page.as_mut() {
+            ledger_page.hide();
+        }
+    }
+
+    /// 切换到统计页面时刷新挂单数据
+    fn on_stats_button_pressed(&mut self) {
+        let tracker = self
+            .weav3r_page
+            .as_ref()
+            .map(|x| x.bind().get_listing_tracker());
+        if let Some(weav3r_page) = self.weav3r_page.as_mut() {
+            weav3r_page.hide();
+        }
+        if let Some(settings_page) = self.settings_page.as_mut() {
+            settings_page.hide();
+        }
+        if let Some(stats_page) = self.stats_page.as_mut() {
+            if let Some(tracker) = tracker {
+                stats_page.bind_mut().set_tracker(tracker);
+            }
+            stats_page.show();
+        }
+        if let Some(ledger_page) = self.ledger_page.as_mut() {
+            ledger_page.hide();
+        }
+    }
+
+    fn on_ledger_button_pressed(&mut self) {
+        if let Some(weav3r_page) = self.weav3r_page.as_mut() {
+            weav3r_page.hide();
+        }
+        if let Some(settings_page) = self.settings_page.as_mut() {
+            settings_page.hide();
+        }
+        if let Some(stats_page) = self.stats_page.as_mut() {
+            stats_page.hide();
+        }
+        if let Some(ledger_page) = self.ledger_page.as_mut() {
+            ledger_page.show();
+        }
     }
 }
@@ -1,3 +1,5 @@
 pub mod weav3r_scene;
 pub mod weav3r_setting_scene;
-pub mod main_scene;
\ No newline at end of file
+pub mod main_scene;
+pub mod listing_stats_scene;
+pub mod ledger_scene;
\ No newline at end of file
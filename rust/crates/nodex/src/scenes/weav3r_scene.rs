@@ -1,32 +1,64 @@
 use godot::{classes::*, prelude::*};
 use godot_toast::ToastPosition;
-use model::weav3r::favorites::FavoritesResponse;
+use model::error::MyError;
 use tools::{
     base::eq_f64,
+    http::CookieJar,
     node::{INodeFunc, INodeTool},
     order_change::OrderChangeDetector,
 };
 use weav3r::{
+    alert::{AlertAction, AlertEvent},
     data::Weav3rSettingData,
+    failure::{self, FailureBackoff, Recovery},
+    history::{HistoryRetention, PriceHistory},
+    points::PointsPriceCache,
+    lifecycle::ListingTracker,
     profit::{FavoritesData, ProfitUserInfo},
 };
 
 use crate::{
     ToastConfig, get_toast_manager,
-    prelude::{Weav3rHttpRequest, Weav3rItem},
+    node::http::torn_api::torn_api_limiter,
+    prelude::{
+        ItemMarketHttpRequest, PointsMarketHttpRequest, PurchasePlanPanel, Weav3rHttpRequest,
+        Weav3rItem,
+    },
 };
 
+/// 点数价格拉取间隔
+const POINTS_REFRESH_SEC: u64 = 300;
+
 #[derive(GodotClass)]
 #[class(init,base=Control)]
 pub struct Weav3rScene {
     #[base]
     base: Base<Control>,
     http_request: Option<Gd<Weav3rHttpRequest>>,
+    /// 物品市场挂单请求，每次轮询请求一个目标物品
+    item_market_request: Option<Gd<ItemMarketHttpRequest>>,
+    item_market_cursor: usize,
+    /// 点数市场请求，价格过期后重新拉取
+    points_request: Option<Gd<PointsMarketHttpRequest>>,
     timer: Option<Gd<Timer>>,
     grid_container: Option<Gd<GridContainer>>,
     audio_player: Option<Gd<AudioStreamPlayer>>,
+    /// 提醒规则指定声音时使用的播放器
+    alert_audio_player: Option<Gd<AudioStreamPlayer>>,
     timer_controller: Option<Gd<Button>>,
+    /// 显示 Torn API 请求数量
+    api_usage_label: Option<Gd<Label>>,
+    api_key: String,
+    plan_panel: Option<Gd<PurchasePlanPanel>>,
     favorites_res: FavoritesData,
+    /// weav3r 暂时性错误的等待时间
+    failure_backoff: FailureBackoff,
+    /// 请求使用的 cookie，保留 Set-Cookie 的过期时间
+    cookie_jar: CookieJar,
+    /// cookie_jar 对应的设置中的 cookie，设置被修改时重新解析
+    cookie_saved: String,
+    /// 当前使用的物品目录拉取时间，目录刷新后重新复制物品 map
+    catalog_fetched_at: Option<u64>,
     /// 每个 item 的期望宽度（用于计算列数）
     #[init(val = 300.0)]
     item_width: f32,
@@ -37,13 +69,18 @@ pub struct Weav3rScene {
 #[godot_api]
 impl IControl for Weav3rScene {
     fn ready(&mut self) {
-        self.favorites_res.filter.office_item_map = torn_logic::item::get_item_info_map().clone();
+        self.sync_item_catalog();
 
         self.http_request = self.get_node_as::<Weav3rHttpRequest>("HTTPRequest");
         self.timer = self.get_node_as::<Timer>("Timer");
         self.grid_container = self.get_node_as::<GridContainer>("%GridContainer");
         self.audio_player = self.get_node_as::<AudioStreamPlayer>("AudioStreamPlayer");
+        let alert_audio_player = AudioStreamPlayer::new_alloc();
+        self.base_mut().add_child(&alert_audio_player);
+        self.alert_audio_player = Some(alert_audio_player);
         self.timer_controller = self.get_node_as::<Button>("%TimerController");
+        self.api_usage_label = self.get_node_as::<Label>("%ApiUsage");
+        self.plan_panel = self.get_node_as::<PurchasePlanPanel>("%PurchasePlanPanel");
 
         if let Some(http) = &self.http_request {
             let http = http.clone();
@@ -53,6 +90,20 @@ impl IControl for Weav3rScene {
         } else {
             godot_error!("Weav3rScene: HTTPRequest node not found.");
         }
+        let item_market_request = ItemMarketHttpRequest::new_alloc();
+        self.base_mut().add_child(&item_market_request);
+        item_market_request
+            .signals()
+            .request_completed()
+            .connect_other(self, Self::on_item_market_completed);
+        self.item_market_request = Some(item_market_request);
+        let points_request = PointsMarketHttpRequest::new_alloc();
+        self.base_mut().add_child(&points_request);
+        points_request
+            .signals()
+            .request_completed()
+            .connect_other(self, Self::on_points_completed);
+        self.points_request = Some(points_request);
 
         let cfg = match tools::cfg::CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
             Ok(r) => r,
@@ -68,6 +119,8 @@ impl IControl for Weav3rScene {
 
         let setting_data = Weav3rSettingData::new(cfg);
         let interval = setting_data.get_interval();
+        self.load_history(&setting_data);
+        self.load_points();
 
         if let Some(timer) = self.timer.as_mut() {
             let mut timer = timer.clone();
@@ -110,6 +163,16 @@ impl INodeFunc for Weav3rScene {
 
 #[godot_api]
 impl Weav3rScene {
+    /// 物品目录刷新后更新过滤使用的物品 map
+    fn sync_item_catalog(&mut self) {
+        let catalog = torn_logic::item::get_catalog();
+        if self.catalog_fetched_at == Some(catalog.fetched_at) {
+            return;
+        }
+        self.catalog_fetched_at = Some(catalog.fetched_at);
+        self.favorites_res.filter.office_item_map = catalog.item_map().as_ref().clone();
+    }
+
     #[func]
     fn send_request(&mut self) {
         let cfg = match tools::cfg::CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
@@ -124,6 +187,7 @@ impl Weav3rScene {
             }
         };
         let setting_data = Weav3rSettingData::new(cfg);
+        self.sync_item_catalog();
         let filter_id_text = setting_data.get_filter_ids();
         if filter_id_text.trim().is_empty() {
             godot_error!("Weav3rScene: FilterIdEdit is empty.");
@@ -137,6 +201,11 @@ impl Weav3rScene {
             .filter_map(|x| x.trim().parse::<i32>().ok())
             .collect::<Vec<i32>>();
         self.favorites_res.filter.target_ids = f_target_ids.clone();
+        torn_api_limiter().budget_per_minute = setting_data.get_torn_api_budget().max(1) as u32;
+        self.api_key = setting_data.get_api_key();
+        self.send_item_market_request(&f_target_ids, &setting_data.get_api_key());
+        self.send_points_request(&setting_data.get_api_key());
+        self.update_api_usage();
 
         let target_ids = torn_logic::item::get_item_list()
             .iter()
@@ -150,51 +219,137 @@ impl Weav3rScene {
             .join(",");
 
         let next_action = setting_data.get_next_action();
-        let cookie = setting_data.get_cookie();
+        self.sync_cookie_jar(&setting_data.get_cookie());
 
+        if self
+            .failure_backoff
+            .is_waiting(tools::time::get_current_time())
+        {
+            return;
+        }
         let Some(http) = self.http_request.as_mut() else {
             godot_error!("Weav3rScene: HTTPRequest node not found.");
             return;
         };
-        http.bind_mut()
-            .send_request(GString::from(&target_ids), next_action, cookie);
+        http.bind_mut().send_request(
+            GString::from(&target_ids),
+            next_action,
+            self.cookie_jar.clone(),
+        );
+    }
+
+    /// 轮流请求目标物品的物品市场挂单，没有 API key 时跳过
+    fn send_item_market_request(&mut self, target_ids: &[i32], key: &str) {
+        if target_ids.is_empty() || key.is_empty() {
+            return;
+        }
+        let item_id = target_ids[self.item_market_cursor % target_ids.len()];
+        let Some(request) = self.item_market_request.as_mut() else {
+            return;
+        };
+        if request.bind_mut().send_request(item_id, key) {
+            self.item_market_cursor = self.item_market_cursor.wrapping_add(1);
+        }
     }
 
     #[func]
-    fn on_request_completed(
+    fn on_item_market_completed(
         &mut self,
         _result: i64,
         response_code: i64,
         _headers: PackedStringArray,
         body: PackedByteArray,
     ) {
-        if let Some(ref mut http) = self.http_request {
-            http.bind_mut().on_request_completed(response_code);
+        let Some(request) = self.item_market_request.as_ref() else {
+            return;
+        };
+        let result = request.bind().parse_response(response_code, body);
+        if let Ok(quotes) = result {
+            self.favorites_res.set_quotes(quotes);
         }
-        if response_code != 200 {
-            godot_error!(
-                "Weav3rScene: Failed to get response.code: {}, body: {}",
-                response_code,
-                String::from_utf8_lossy(body.as_slice()).to_string()
-            );
-            self.on_timer_controller_pressed();
-            self.toast("请求失败".to_string());
+        self.update_api_usage();
+    }
+
+    /// 显示当前 key 最近一分钟的 Torn API 请求数量
+    fn update_api_usage(&mut self) {
+        let Some(label) = self.api_usage_label.as_mut() else {
+            return;
+        };
+        if self.api_key.is_empty() {
             return;
         }
+        let key = self.api_key.as_str();
+        let now = tools::time::get_current_time();
+        let mut limiter = torn_api_limiter();
+        let text = format!(
+            "API {}/{} per min, queued: {}",
+            limiter.used_this_minute(key, now),
+            limiter.budget_per_minute,
+            limiter.queued(key, now)
+        );
+        label.set_text(text.as_str());
+    }
 
-        let response_text = String::from_utf8_lossy(body.as_slice());
-        // 如果响应结果不是0开头，则视为请求失败
-        if !response_text.starts_with('0') {
-            godot_error!(
-                "Weav3rScene: Request failed - response does not start with '0'. Response: {}",
-                if response_text.len() > 100 {
-                    format!("{}...", &response_text[..100])
-                } else {
-                    response_text.to_string()
-                }
-            );
+    /// 点数价格过期时重新拉取
+    fn send_points_request(&mut self, key: &str) {
+        let now = tools::time::get_current_time();
+        if key.is_empty() || !self.favorites_res.points.is_stale(now, POINTS_REFRESH_SEC) {
             return;
         }
+        if let Some(request) = self.points_request.as_mut() {
+            request.bind_mut().send_request(key);
+        }
+    }
+
+    #[func]
+    fn on_points_completed(
+        &mut self,
+        _result: i64,
+        response_code: i64,
+        _headers: PackedStringArray,
+        body: PackedByteArray,
+    ) {
+        let Some(request) = self.points_request.as_ref() else {
+            return;
+        };
+        let result = request.bind().parse_response(response_code, body);
+        let Ok(price) = result else {
+            return;
+        };
+        godot_print!("Weav3rScene: Points price: {}", price.price);
+        if self.favorites_res.points.record(price) {
+            self.save_points();
+        }
+    }
+
+    #[func]
+    fn on_request_completed(
+        &mut self,
+        _result: i64,
+        response_code: i64,
+        headers: PackedStringArray,
+        body: PackedByteArray,
+    ) {
+        if let Some(ref mut http) = self.http_request {
+            http.bind_mut().on_request_completed(response_code);
+        }
+        let response_text = String::from_utf8_lossy(body.as_slice());
+        let headers: Vec<String> = headers.as_slice().iter().map(|x| x.to_string()).collect();
+        self.apply_set_cookie(&headers);
+        let favorites_response =
+            match failure::parse_response(response_code, &headers, &response_text) {
+                Ok(r) => r,
+                Err(err) => {
+                    godot_error!(
+                        "Weav3rScene: Request failed: {:?}, body: {}",
+                        err,
+                        response_text.chars().take(500).collect::<String>()
+                    );
+                    self.on_weav3r_failure(err);
+                    return;
+                }
+            };
+        self.failure_backoff.on_success();
 
         let cfg = match tools::cfg::CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
             Ok(r) => r,
@@ -238,26 +393,26 @@ impl Weav3rScene {
         self.favorites_res.filter.min_profit_percentage = setting_data.get_profit_percent();
         self.favorites_res.filter.office_sell_price = setting_data.get_office_sell_price() as u64;
         self.favorites_res.filter.office_sell_profit = setting_data.get_office_sell_profit() as u64;
+        self.favorites_res.filter.filter_items = setting_data.get_filter_items();
+        self.favorites_res.fee = setting_data.get_sell_fees();
+        self.favorites_res.filter.min_confidence = setting_data.get_min_confidence();
+        self.favorites_res.filter.players = setting_data.get_players();
+        self.favorites_res.filter.equipment_mode = setting_data.get_equipment_mode();
         self.favorites_res.sort.recent_sec = setting_data.get_light_sec() as u64;
-
-        let favorites_response = match FavoritesResponse::from_text(&response_text) {
-            Ok(r) => r,
-            Err(err) => {
-                godot_error!(
-                    "Weav3rScene: Failed to parse favorites response. Error: {:?}",
-                    err,
-                );
-                godot_error!(
-                    "Weav3rScene: Original response text (first 500 chars): {}",
-                    if response_text.len() > 500 {
-                        format!("{}...", &response_text[..500])
-                    } else {
-                        response_text.to_string()
-                    }
-                );
-                return;
-            }
-        };
+        self.favorites_res.sort.keys = setting_data.get_sort_strategy();
+        self.favorites_res.alerts.rules = setting_data.get_alert_rules();
+        self.favorites_res.points_price = setting_data.get_points_price().max(0) as u64;
+
+        self.favorites_res.price_reference = setting_data.get_price_reference();
+        self.favorites_res.history.retention = Self::history_retention(&setting_data);
+        let now = tools::time::get_current_time();
+        if self
+            .favorites_res
+            .history
+            .record(&favorites_response.items, now)
+        {
+            self.save_history();
+        }
         self.favorites_res.set_new_profit(favorites_response.items);
         if let Some(audio_player) = self.audio_player.as_mut()
             && self.favorites_res.has_new
@@ -266,9 +421,123 @@ impl Weav3rScene {
             godot_print!("Weav3rScene: Has new data.");
             audio_player.play();
         }
+        for event in self.favorites_res.alert_events.clone() {
+            self.run_alert(&event);
+        }
+        if let Some(plan_panel) = self.plan_panel.as_mut() {
+            plan_panel
+                .bind_mut()
+                .set_items(self.favorites_res.plan_listings.clone());
+        }
         self.render_list(self.favorites_res.user_profit_result.clone());
     }
 
+    /// 执行提醒规则的动作，置顶已经在排序时处理
+    fn run_alert(&mut self, event: &AlertEvent) {
+        for action in event.actions.iter() {
+            match action {
+                AlertAction::Sound(path) if path.is_empty() => {
+                    if let Some(audio_player) = self.audio_player.as_mut() {
+                        audio_player.play();
+                    }
+                }
+                AlertAction::Sound(path) => {
+                    let Ok(stream) = try_load::<AudioStream>(path.as_str()) else {
+                        godot_error!("Weav3rScene: Failed to load alert sound: {}", path);
+                        continue;
+                    };
+                    if let Some(audio_player) = self.alert_audio_player.as_mut() {
+                        audio_player.set_stream(&stream);
+                        audio_player.play();
+                    }
+                }
+                AlertAction::Toast => self.toast(event.message.clone()),
+                AlertAction::Pin => {}
+                AlertAction::Log => godot_print!("Weav3rScene: Alert {}", event.message),
+            }
+        }
+    }
+
+    /// 挂单生命周期数据，用于统计页面
+    pub fn get_listing_tracker(&self) -> ListingTracker {
+        self.favorites_res.lifecycle.clone()
+    }
+
+    fn history_retention(setting_data: &Weav3rSettingData) -> HistoryRetention {
+        HistoryRetention {
+            max_age_sec: setting_data.get_history_days().max(1) as u64 * 24 * 3600,
+            ..Default::default()
+        }
+    }
+
+    /// 读取本地价格历史
+    fn load_history(&mut self, setting_data: &Weav3rSettingData) {
+        let retention = Self::history_retention(setting_data);
+        let Some(json) = tools::file::read_text(PriceHistory::HISTORY_PATH) else {
+            self.favorites_res.history.retention = retention;
+            return;
+        };
+        match PriceHistory::from_json(&json, retention) {
+            Ok(history) => self.favorites_res.history = history,
+            Err(err) => godot_error!(
+                "Weav3rScene: Failed to parse {:?}: {:?}",
+                PriceHistory::HISTORY_PATH,
+                err
+            ),
+        }
+    }
+
+    /// 加载本地点数价格缓存
+    fn load_points(&mut self) {
+        let Some(json) = tools::file::read_text(PointsPriceCache::CACHE_PATH) else {
+            return;
+        };
+        match PointsPriceCache::from_json(&json) {
+            Ok(points) => self.favorites_res.points = points,
+            Err(err) => godot_error!(
+                "Weav3rScene: Failed to parse {:?}: {:?}",
+                PointsPriceCache::CACHE_PATH,
+                err
+            ),
+        }
+    }
+
+    /// 保存点数价格缓存
+    fn save_points(&self) {
+        let json = match self.favorites_res.points.to_json() {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!("Weav3rScene: Failed to serialize points price: {:?}", err);
+                return;
+            }
+        };
+        if let Err(err) = tools::file::write_text(PointsPriceCache::CACHE_PATH, &json) {
+            godot_error!(
+                "Weav3rScene: Failed to save {:?}: {:?}",
+                PointsPriceCache::CACHE_PATH,
+                err
+            );
+        }
+    }
+
+    /// 保存本地价格历史
+    fn save_history(&self) {
+        let json = match self.favorites_res.history.to_json() {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!("Weav3rScene: Failed to serialize price history: {:?}", err);
+                return;
+            }
+        };
+        if let Err(err) = tools::file::write_text(PriceHistory::HISTORY_PATH, &json) {
+            godot_error!(
+                "Weav3rScene: Failed to save {:?}: {:?}",
+                PriceHistory::HISTORY_PATH,
+                err
+            );
+        }
+    }
+
     fn render_list(&mut self, items: Vec<ProfitUserInfo>) {
         if items.is_empty() {
             godot_print!("Weav3rScene: No items to render");
@@ -391,7 +660,13 @@ impl Weav3rScene {
     }
 
     fn update_columns(&mut self) {
-        let available_width = self.base().get_size().x;
+        let mut available_width = self.base().get_size().x;
+        // 扣除购买计划面板的宽度
+        if let Some(plan_panel) = self.plan_panel.as_ref()
+            && plan_panel.is_visible()
+        {
+            available_width -= plan_panel.get_size().x;
+        }
         let columns = (available_width / self.item_width).max(1.0) as i32;
 
         let Some(grid_container) = self.grid_container.as_mut() else {
@@ -414,6 +689,68 @@ impl Weav3rScene {
         }
     }
 
+    /// 设置中的 cookie 被修改时重新解析
+    fn sync_cookie_jar(&mut self, saved: &str) {
+        if self.cookie_saved != saved {
+            self.cookie_jar = CookieJar::parse(saved);
+            self.cookie_saved = saved.to_string();
+        }
+    }
+
+    /// 服务端更新 cookie 时保存到设置，避免保存的 cookie 过期
+    /// 只更新过期时间时不保存，过期时间只保留在 cookie_jar 中
+    fn apply_set_cookie(&mut self, headers: &[String]) {
+        let cfg = match tools::cfg::CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "Weav3rScene: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+                return;
+            }
+        };
+        let mut setting_data = Weav3rSettingData::new(cfg);
+        self.sync_cookie_jar(&setting_data.get_cookie());
+        let now = tools::time::get_current_time();
+        if self.cookie_jar.apply_headers(headers, now) {
+            godot_print!("Weav3rScene: Cookie updated by Set-Cookie.");
+            let cookie = self.cookie_jar.to_header(now);
+            setting_data.set_cookie(&cookie);
+            self.cookie_saved = cookie;
+            if let Err(err) = setting_data.save() {
+                godot_error!(
+                    "Weav3rScene: Failed to save {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+            }
+        }
+    }
+
+    /// 需要更新设置时停止轮询并打开设置页面，暂时性错误等待后继续轮询
+    fn on_weav3r_failure(&mut self, err: MyError) {
+        self.toast(failure::user_message(&err));
+        match failure::recovery(&err) {
+            Recovery::OpenSettings => {
+                if self.timer.as_ref().is_some_and(|x| !x.is_paused()) {
+                    self.on_timer_controller_pressed();
+                }
+                self.signals().open_settings().emit();
+            }
+            Recovery::Backoff => {
+                let now = tools::time::get_current_time();
+                let until = self.failure_backoff.on_failure(&err, now);
+                godot_print!("Weav3rScene: Retry after {}s", until.saturating_sub(now));
+            }
+        }
+    }
+
+    /// 请求需要用户更新 next-action 或 cookie
+    #[signal]
+    fn open_settings();
+
     #[func]
     fn pause_timer(&mut self) {
         if let Some(timer) = self.timer.as_mut() {
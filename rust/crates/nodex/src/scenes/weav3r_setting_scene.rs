@@ -1,12 +1,26 @@
 use godot::{
-    classes::{Button, CheckButton, Control, DisplayServer, IControl, SpinBox, TextEdit},
+    classes::{
+        Button, CheckButton, Control, DisplayServer, IControl, Label, LineEdit, OptionButton,
+        SpinBox, TextEdit, Time,
+    },
     prelude::*,
 };
 use tools::{
     cfg::CfgTool,
     node::{INodeFunc, INodeTool},
 };
-use weav3r::data::Weav3rSettingData;
+use torn_logic::item::{CatalogSource, ITEM_CACHE_PATH, ItemCatalog};
+use weav3r::{
+    alert::AlertRule,
+    data::Weav3rSettingData,
+    fee::FeeSchedule,
+    history::PriceReference,
+    player::PlayerList,
+    profit::{EquipmentMode, FilterItem},
+    sort::{SORT_PRESETS, SortKey},
+};
+
+use crate::node::http::torn_items::TornItemsHttpRequest;
 
 #[derive(GodotClass)]
 #[class(init,base=Control)]
@@ -19,13 +33,29 @@ pub struct Weav3rSettingScene {
     audio_switch_btn: Option<Gd<CheckButton>>, // 音频开关
     profit_percent_edit: Option<Gd<SpinBox>>,
     min_profit_edit: Option<Gd<SpinBox>>,
+    min_confidence_edit: Option<Gd<SpinBox>>,        // 最低可信度
+    equipment_base_value_btn: Option<Gd<CheckButton>>, // 武器和防具只按基础价值计算
+    sort_preset_option: Option<Gd<OptionButton>>,    // 预设排序方式
+    sort_strategy_edit: Option<Gd<LineEdit>>,        // 排序条件
     filter_id_edit: Option<Gd<TextEdit>>,
+    filter_items_edit: Option<Gd<TextEdit>>, // 单个物品过滤条件
+    sell_fee_edit: Option<Gd<TextEdit>>,     // 出售费用
+    players_edit: Option<Gd<TextEdit>>,      // 玩家屏蔽、收藏和备注
+    alert_rules_edit: Option<Gd<TextEdit>>,  // 提醒规则
+    price_reference_edit: Option<Gd<LineEdit>>, // 参考价格
+    history_days_edit: Option<Gd<SpinBox>>,  // 价格历史保留天数
     office_sell_price_edit: Option<Gd<SpinBox>>,
+    points_price_edit: Option<Gd<SpinBox>>, // 没有拉取到点数价格时使用
+    api_budget_edit: Option<Gd<SpinBox>>,
     office_sell_profit_edit: Option<Gd<SpinBox>>,
     token_edit: Option<Gd<TextEdit>>,
     cookie_edit: Option<Gd<TextEdit>>,
+    api_key_edit: Option<Gd<LineEdit>>, // Torn API key
     parse_weav3r_curl_btn: Option<Gd<Button>>,
     save_button: Option<Gd<Button>>,
+    refresh_catalog_button: Option<Gd<Button>>,
+    catalog_status_label: Option<Gd<Label>>, // 物品目录来源和更新时间
+    torn_items_request: Option<Gd<TornItemsHttpRequest>>,
 }
 
 #[godot_api]
@@ -37,14 +67,29 @@ impl IControl for Weav3rSettingScene {
         self.audio_switch_btn = self.get_node_as::<CheckButton>("%AudioSwitchBtn");
         self.profit_percent_edit = self.get_node_as::<SpinBox>("%ProfitPercentEdit");
         self.min_profit_edit = self.get_node_as::<SpinBox>("%MinProfitEdit");
+        self.min_confidence_edit = self.get_node_as::<SpinBox>("%MinConfidenceEdit");
+        self.equipment_base_value_btn = self.get_node_as::<CheckButton>("%EquipmentBaseValueBtn");
+        self.sort_preset_option = self.get_node_as::<OptionButton>("%SortPresetOption");
+        self.sort_strategy_edit = self.get_node_as::<LineEdit>("%SortStrategyEdit");
         self.filter_id_edit = self.get_node_as::<TextEdit>("%FilterIdEdit");
+        self.filter_items_edit = self.get_node_as::<TextEdit>("%FilterItemsEdit");
+        self.sell_fee_edit = self.get_node_as::<TextEdit>("%SellFeeEdit");
+        self.players_edit = self.get_node_as::<TextEdit>("%PlayersEdit");
+        self.alert_rules_edit = self.get_node_as::<TextEdit>("%AlertRulesEdit");
+        self.price_reference_edit = self.get_node_as::<LineEdit>("%PriceReferenceEdit");
+        self.history_days_edit = self.get_node_as::<SpinBox>("%HistoryDaysEdit");
         self.office_sell_price_edit = self.get_node_as::<SpinBox>("%OfficeSellPriceEdit");
+        self.points_price_edit = self.get_node_as::<SpinBox>("%PointsPriceEdit");
+        self.api_budget_edit = self.get_node_as::<SpinBox>("%ApiBudgetEdit");
         self.office_sell_profit_edit = self.get_node_as::<SpinBox>("%OfficeSellProfitEdit");
         self.token_edit = self.get_node_as::<TextEdit>("%TokenEdit");
         self.cookie_edit = self.get_node_as::<TextEdit>("%CookieEdit");
+        self.api_key_edit = self.get_node_as::<LineEdit>("%ApiKeyEdit");
         self.parse_weav3r_curl_btn = self.get_node_as::<Button>("%ParseWeav3rCurlBtna");
 
         self.save_button = self.get_node_as::<Button>("%SaveButton");
+        self.refresh_catalog_button = self.get_node_as::<Button>("%RefreshCatalogButton");
+        self.catalog_status_label = self.get_node_as::<Label>("%CatalogStatus");
 
         let cfg = match CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
             Ok(r) => r,
@@ -90,18 +135,103 @@ impl IControl for Weav3rSettingScene {
         } else {
             godot_error!("Weav3rSettingScene: MinProfitEdit node not found.");
         }
+        if let Some(edit) = self.min_confidence_edit.as_mut() {
+            let min_confidence = setting_data.get_min_confidence();
+            edit.set_value(min_confidence as f64);
+        } else {
+            godot_error!("Weav3rSettingScene: MinConfidenceEdit node not found.");
+        }
+        if let Some(btn) = self.equipment_base_value_btn.as_mut() {
+            btn.set_pressed(setting_data.get_equipment_mode() == EquipmentMode::BaseValue);
+        } else {
+            godot_error!("Weav3rSettingScene: EquipmentBaseValueBtn node not found.");
+        }
+        let sort_strategy = SortKey::format_list(&setting_data.get_sort_strategy());
+        if let Some(edit) = self.sort_strategy_edit.as_mut() {
+            edit.set_text(sort_strategy.as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: SortStrategyEdit node not found.");
+        }
+        if let Some(option) = self.sort_preset_option.as_mut() {
+            option.clear();
+            for (name, _) in SORT_PRESETS {
+                option.add_item(name);
+            }
+            // 自定义排序时不选中任何预设
+            let selected = SORT_PRESETS
+                .iter()
+                .position(|(_, keys)| *keys == sort_strategy)
+                .map(|x| x as i32)
+                .unwrap_or(-1);
+            option.select(selected);
+            option
+                .clone()
+                .signals()
+                .item_selected()
+                .connect_other(self, Self::on_sort_preset_selected);
+        } else {
+            godot_error!("Weav3rSettingScene: SortPresetOption node not found.");
+        }
         if let Some(filter_id_edit) = self.filter_id_edit.as_mut() {
             let filter_id = setting_data.get_filter_ids();
             filter_id_edit.set_text(filter_id.as_str());
         } else {
             godot_error!("Weav3rSettingScene: FilterIdEdit node not found.");
         }
+        if let Some(filter_items_edit) = self.filter_items_edit.as_mut() {
+            let filter_items = setting_data.get_filter_items();
+            filter_items_edit.set_text(FilterItem::format_lines(&filter_items).as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: FilterItemsEdit node not found.");
+        }
+        if let Some(sell_fee_edit) = self.sell_fee_edit.as_mut() {
+            let fees = setting_data.get_sell_fees();
+            sell_fee_edit.set_text(fees.format_lines().as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: SellFeeEdit node not found.");
+        }
+        if let Some(players_edit) = self.players_edit.as_mut() {
+            let players = setting_data.get_players();
+            players_edit.set_text(players.format_lines().as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: PlayersEdit node not found.");
+        }
+        if let Some(alert_rules_edit) = self.alert_rules_edit.as_mut() {
+            let rules = setting_data.get_alert_rules();
+            alert_rules_edit.set_text(AlertRule::format_lines(&rules).as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: AlertRulesEdit node not found.");
+        }
+        if let Some(edit) = self.price_reference_edit.as_mut() {
+            let reference = setting_data.get_price_reference();
+            edit.set_text(reference.format().as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: PriceReferenceEdit node not found.");
+        }
+        if let Some(edit) = self.history_days_edit.as_mut() {
+            let days = setting_data.get_history_days();
+            edit.set_value(days as f64);
+        } else {
+            godot_error!("Weav3rSettingScene: HistoryDaysEdit node not found.");
+        }
         if let Some(edit) = self.office_sell_price_edit.as_mut() {
             let value = setting_data.get_office_sell_price();
             edit.set_value(value as f64);
         } else {
             godot_error!("Weav3rSettingScene: office_sell_price_edit node not found.");
         }
+        if let Some(edit) = self.points_price_edit.as_mut() {
+            let value = setting_data.get_points_price();
+            edit.set_value(value as f64);
+        } else {
+            godot_error!("Weav3rSettingScene: points_price_edit node not found.");
+        }
+        if let Some(edit) = self.api_budget_edit.as_mut() {
+            let value = setting_data.get_torn_api_budget();
+            edit.set_value(value as f64);
+        } else {
+            godot_error!("Weav3rSettingScene: api_budget_edit node not found.");
+        }
         if let Some(edit) = self.office_sell_profit_edit.as_mut() {
             let value = setting_data.get_office_sell_profit();
             edit.set_value(value as f64);
@@ -120,6 +250,12 @@ impl IControl for Weav3rSettingScene {
         } else {
             godot_error!("Weav3rSettingScene: CookieEdit node not found.");
         }
+        if let Some(api_key_edit) = self.api_key_edit.as_mut() {
+            let api_key = setting_data.get_api_key();
+            api_key_edit.set_text(api_key.as_str());
+        } else {
+            godot_error!("Weav3rSettingScene: ApiKeyEdit node not found.");
+        }
 
         if let Some(save_button) = &self.save_button {
             let save_button = save_button.clone();
@@ -139,6 +275,25 @@ impl IControl for Weav3rSettingScene {
         } else {
             godot_error!("Weav3rSettingScene: SaveWeav3rTokenBtn node not found.");
         }
+        if let Some(refresh_catalog_button) = &self.refresh_catalog_button {
+            let refresh_catalog_button = refresh_catalog_button.clone();
+            refresh_catalog_button
+                .signals()
+                .pressed()
+                .connect_other(self, Self::on_refresh_catalog_pressed);
+        }
+        let torn_items_request = TornItemsHttpRequest::new_alloc();
+        self.base_mut().add_child(&torn_items_request);
+        torn_items_request
+            .signals()
+            .request_completed()
+            .connect_other(self, Self::on_torn_items_completed);
+        self.torn_items_request = Some(torn_items_request);
+        self.update_catalog_status();
+        self.to_gd()
+            .signals()
+            .visibility_changed()
+            .connect_other(self, Self::on_visibility_changed);
     }
 }
 
@@ -184,14 +339,75 @@ impl Weav3rSettingScene {
             let min_profit = min_profit_edit.get_value().round() as i64;
             setting_data.set_min_profit(min_profit);
         }
+        if let Some(edit) = &self.min_confidence_edit {
+            let min_confidence = edit.get_value() as f32;
+            setting_data.set_min_confidence(min_confidence);
+        }
+        if let Some(btn) = &self.equipment_base_value_btn {
+            let mode = if btn.is_pressed() {
+                EquipmentMode::BaseValue
+            } else {
+                EquipmentMode::Exclude
+            };
+            setting_data.set_equipment_mode(mode);
+        }
+        if let Some(edit) = &self.sort_strategy_edit {
+            let text = edit.get_text().to_string();
+            setting_data.set_sort_strategy(&SortKey::parse_list(&text));
+        }
         if let Some(filter_id_edit) = &self.filter_id_edit {
             let filter_id = filter_id_edit.get_text().strip_edges();
             setting_data.set_filter_ids(&filter_id.to_string());
         }
+        if let Some(filter_items_edit) = &self.filter_items_edit {
+            let text = filter_items_edit.get_text().to_string();
+            let item_map = torn_logic::item::get_item_info_map();
+            // 名称为空时从物品列表中补全
+            let filter_items = FilterItem::parse_lines(&text)
+                .into_iter()
+                .map(|mut x| {
+                    if x.name.is_empty()
+                        && let Some(info) = item_map.get(&x.id)
+                    {
+                        x.name = info.name.clone();
+                    }
+                    x
+                })
+                .collect::<Vec<FilterItem>>();
+            setting_data.set_filter_items(&filter_items);
+        }
+        if let Some(sell_fee_edit) = &self.sell_fee_edit {
+            let text = sell_fee_edit.get_text().to_string();
+            setting_data.set_sell_fees(&FeeSchedule::parse_lines(&text));
+        }
+        if let Some(players_edit) = &self.players_edit {
+            let text = players_edit.get_text().to_string();
+            setting_data.set_players(&PlayerList::parse_lines(&text));
+        }
+        if let Some(alert_rules_edit) = &self.alert_rules_edit {
+            let text = alert_rules_edit.get_text().to_string();
+            setting_data.set_alert_rules(&AlertRule::parse_lines(&text));
+        }
+        if let Some(edit) = &self.price_reference_edit {
+            let text = edit.get_text().to_string();
+            setting_data.set_price_reference(PriceReference::parse(&text));
+        }
+        if let Some(edit) = &self.history_days_edit {
+            let days = edit.get_value().round() as i64;
+            setting_data.set_history_days(days);
+        }
         if let Some(office_sell_price_edit) = &self.office_sell_price_edit {
             let office_sell_price = office_sell_price_edit.get_value().round() as i64;
             setting_data.set_office_sell_price(office_sell_price);
         }
+        if let Some(points_price_edit) = &self.points_price_edit {
+            let points_price = points_price_edit.get_value().round() as i64;
+            setting_data.set_points_price(points_price);
+        }
+        if let Some(api_budget_edit) = &self.api_budget_edit {
+            let budget = api_budget_edit.get_value().round() as i64;
+            setting_data.set_torn_api_budget(budget);
+        }
         if let Some(office_sell_profit_edit) = &self.office_sell_profit_edit {
             let office_sell_profit = office_sell_profit_edit.get_value() as i64;
             setting_data.set_office_sell_profit(office_sell_profit);
@@ -204,6 +420,10 @@ impl Weav3rSettingScene {
             let cookie = cookie_edit.get_text().strip_edges();
             setting_data.set_cookie(&cookie.to_string());
         }
+        if let Some(api_key_edit) = &self.api_key_edit {
+            let api_key = api_key_edit.get_text().strip_edges();
+            setting_data.set_api_key(&api_key.to_string());
+        }
 
         if let Err(err) = setting_data.save() {
             godot_error!(
@@ -216,6 +436,112 @@ impl Weav3rSettingScene {
         }
     }
 
+    /// cookie 可能被响应中的 Set-Cookie 更新，显示时重新读取
+    #[func]
+    fn on_visibility_changed(&mut self) {
+        if !self.base().is_visible() {
+            return;
+        }
+        let cfg = match CfgTool::new(Weav3rSettingData::SETTINGS_PATH) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "Weav3rSettingScene: Failed to load {:?}: {:?}",
+                    Weav3rSettingData::SETTINGS_PATH,
+                    err
+                );
+                return;
+            }
+        };
+        let setting_data = Weav3rSettingData::new(cfg);
+        if let Some(cookie_edit) = self.cookie_edit.as_mut() {
+            cookie_edit.set_text(setting_data.get_cookie().as_str());
+        }
+    }
+
+    /// 从 Torn API 重新拉取物品目录
+    #[func]
+    fn on_refresh_catalog_pressed(&mut self) {
+        let key = self
+            .api_key_edit
+            .as_ref()
+            .map(|x| x.get_text().to_string())
+            .unwrap_or_default();
+        let Some(request) = self.torn_items_request.as_mut() else {
+            return;
+        };
+        if !request.bind_mut().send_request(key.trim()) {
+            godot_warn!("Weav3rSettingScene: Failed to refresh item catalog.");
+        }
+    }
+
+    #[func]
+    fn on_torn_items_completed(
+        &mut self,
+        _result: i64,
+        response_code: i64,
+        _headers: PackedStringArray,
+        body: PackedByteArray,
+    ) {
+        let Some(request) = self.torn_items_request.as_ref() else {
+            return;
+        };
+        let items = match request.bind().parse_response(response_code, body) {
+            Ok(r) => r,
+            Err(err) => {
+                godot_error!(
+                    "Weav3rSettingScene: Failed to refresh item catalog: {:?}",
+                    err
+                );
+                return;
+            }
+        };
+        let catalog = ItemCatalog::new(
+            items,
+            CatalogSource::TornApi,
+            tools::time::get_current_time(),
+        );
+        match catalog.to_json() {
+            Ok(json) => {
+                if let Err(err) = tools::file::write_text(ITEM_CACHE_PATH, &json) {
+                    godot_error!(
+                        "Weav3rSettingScene: Failed to save {:?}: {:?}",
+                        ITEM_CACHE_PATH,
+                        err
+                    );
+                }
+            }
+            Err(err) => godot_error!("Weav3rSettingScene: Failed to serialize catalog: {:?}", err),
+        }
+        torn_logic::item::set_catalog(catalog);
+        self.update_catalog_status();
+    }
+
+    fn update_catalog_status(&mut self) {
+        let catalog = torn_logic::item::get_catalog();
+        let text = match catalog.source {
+            CatalogSource::Bundled => format!("Items: {} (bundled)", catalog.items().len()),
+            CatalogSource::TornApi => format!(
+                "Items: {} (Torn API, {})",
+                catalog.items().len(),
+                Time::singleton().get_datetime_string_from_unix_time(catalog.fetched_at as i64)
+            ),
+        };
+        if let Some(label) = self.catalog_status_label.as_mut() {
+            label.set_text(text.as_str());
+        }
+    }
+
+    #[func]
+    fn on_sort_preset_selected(&mut self, index: i64) {
+        let Some((_, keys)) = SORT_PRESETS.get(index as usize) else {
+            return;
+        };
+        if let Some(edit) = self.sort_strategy_edit.as_mut() {
+            edit.set_text(*keys);
+        }
+    }
+
     #[func]
     pub fn on_save_weav3r_token_pressed(&mut self) {
         let display_server = DisplayServer::singleton();
@@ -1,4 +1,7 @@
-use godot::{classes::DirAccess, global};
+use godot::{
+    classes::{DirAccess, FileAccess, file_access::ModeFlags},
+    global,
+};
 
 pub fn create_folder(url: &str) -> Option<global::Error> {
     let e = DirAccess::make_dir_recursive_absolute(url);
@@ -7,3 +10,27 @@ pub fn create_folder(url: &str) -> Option<global::Error> {
         _ => Some(e),
     }
 }
+
+/// 读取文本文件，文件不存在时返回 None
+pub fn read_text(path: &str) -> Option<String> {
+    if !FileAccess::file_exists(path) {
+        return None;
+    }
+    Some(FileAccess::get_file_as_string(path).to_string())
+}
+
+/// 写入文本文件，先写入临时文件再重命名，避免写一半时文件损坏
+pub fn write_text(path: &str, text: &str) -> Result<(), global::Error> {
+    let tmp_path = format!("{}.tmp", path);
+    let Some(mut file) = FileAccess::open(tmp_path.as_str(), ModeFlags::WRITE) else {
+        return Err(FileAccess::get_open_error());
+    };
+    if !file.store_string(text) {
+        return Err(global::Error::ERR_FILE_CANT_WRITE);
+    }
+    file.close();
+    match DirAccess::rename_absolute(tmp_path.as_str(), path) {
+        global::Error::OK => Ok(()),
+        e => Err(e),
+    }
+}
@@ -1,5 +1,13 @@
 use std::collections::HashMap;
 
+pub use cookie::*;
+
+mod cookie;
+pub mod url;
+
+use url::{build_url, parse_query};
+
+#[cfg(feature = "godot")]
 use godot::{
     classes::{
         HttpRequest,
@@ -35,6 +43,7 @@ impl HttpMethod {
     }
 }
 
+#[cfg(feature = "godot")]
 impl From<http_client::Method> for HttpMethod {
     fn from(method: http_client::Method) -> Self {
         match method {
@@ -50,6 +59,7 @@ impl From<http_client::Method> for HttpMethod {
     }
 }
 
+#[cfg(feature = "godot")]
 impl From<HttpMethod> for http_client::Method {
     fn from(method: HttpMethod) -> Self {
         match method {
@@ -66,11 +76,14 @@ impl From<HttpMethod> for http_client::Method {
 
 #[derive(Clone)]
 pub struct HttpTool {
-    pub params: HashMap<String, String>,
+    /// 查询参数，按加入的顺序追加到地址后面，可以有重复的参数
+    pub params: Vec<(String, String)>,
     pub headers: HashMap<String, String>,
     pub body: Vec<u8>,
     pub method: HttpMethod,
     pub url: String,
+    /// 不为空时用于生成 Cookie 头，替换 headers 中的 cookie
+    pub cookies: CookieJar,
 }
 
 impl std::fmt::Debug for HttpTool {
@@ -88,7 +101,8 @@ impl std::fmt::Debug for HttpTool {
         writeln!(f, "  headers: {:?},", self.headers)?;
         writeln!(f, "  body: {},", body_str)?;
         writeln!(f, "  method: {:?},", self.method)?;
-        writeln!(f, "  url: {:?}", self.url)?;
+        writeln!(f, "  url: {:?},", self.url)?;
+        writeln!(f, "  cookies: {:?}", self.cookies)?;
         write!(f, "}}")
     }
 }
@@ -96,35 +110,56 @@ impl std::fmt::Debug for HttpTool {
 impl Default for HttpTool {
     fn default() -> Self {
         Self {
-            params: HashMap::new(),
+            params: Vec::new(),
             headers: HashMap::new(),
             body: Vec::new(),
             method: HttpMethod::GET,
             url: String::new(),
+            cookies: CookieJar::default(),
         }
     }
 }
 
 impl HttpTool {
     pub fn add_param(&mut self, key: &str, value: &str) {
-        self.params.insert(key.to_string(), value.to_string());
+        self.params.push((key.to_string(), value.to_string()));
     }
 
     pub fn add_header(&mut self, key: &str, value: &str) {
         self.headers.insert(key.to_string(), value.to_string());
     }
 
-    pub fn set_params(&mut self, params: HashMap<String, String>) {
+    pub fn set_params(&mut self, params: Vec<(String, String)>) {
         self.params = params;
     }
     pub fn set_headers(&mut self, headers: HashMap<String, String>) {
         self.headers = headers;
     }
 
+    pub fn set_cookies(&mut self, cookies: CookieJar) {
+        self.cookies = cookies;
+    }
+
+    /// 请求头列表，格式为 `Name: value`，cookie 不为空时由 cookies 生成 Cookie 头
+    pub fn header_lines(&self, now: u64) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .headers
+            .iter()
+            .filter(|(key, _)| self.cookies.is_empty() || !key.eq_ignore_ascii_case("cookie"))
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect();
+        let cookie = self.cookies.to_header(now);
+        if !cookie.is_empty() {
+            lines.push(format!("Cookie: {}", cookie));
+        }
+        lines
+    }
+
     pub fn set_body(&mut self, body: Vec<u8>) {
         self.body = body;
     }
 
+    #[cfg(feature = "godot")]
     pub fn set_method(&mut self, method: http_client::Method) {
         self.method = HttpMethod::from(method);
     }
@@ -133,6 +168,11 @@ impl HttpTool {
         self.url = url.to_string();
     }
 
+    /// 实际请求的地址，url 中已有的查询字符串在前，params 编码后追加在后面
+    pub fn full_url(&self) -> String {
+        build_url(&self.url, &self.params)
+    }
+
     pub fn from_curl(curl_command: &str) -> Result<Self, String> {
         let mut tool = HttpTool::default();
         let mut method = HttpMethod::GET;
@@ -140,6 +180,7 @@ impl HttpTool {
         let mut headers: HashMap<String, String> = HashMap::new();
         let mut data: Option<String> = None;
         let mut form_data: Vec<String> = Vec::new();
+        let mut get_data = false;
 
         let tokens: Vec<String> = tokenize_curl(curl_command);
 
@@ -200,6 +241,9 @@ impl HttpTool {
                 "--compressed" => {
                     headers.insert("accept-encoding".to_string(), "gzip, deflate".to_string());
                 }
+                "-G" | "--get" => {
+                    get_data = true;
+                }
                 "-k" | "--insecure" => {}
                 "-s" | "--silent" | "-v" | "--verbose" | "-i" | "--include" | "-L"
                 | "--location" | "-f" | "--fail" | "--max-time" | "--connect-timeout" => {}
@@ -208,26 +252,21 @@ impl HttpTool {
                         let flag = token.strip_prefix("-").unwrap_or("");
                         for c in flag.chars() {
                             match c {
-                                'X' => {
-                                    if i + 1 < tokens.len() {
-                                        method = HttpMethod::from_str(&tokens[i + 1]);
-                                        i += 1;
-                                    }
+                                'X' if i + 1 < tokens.len() => {
+                                    method = HttpMethod::from_str(&tokens[i + 1]);
+                                    i += 1;
                                 }
-                                'H' => {
-                                    if i + 1 < tokens.len() {
-                                        if let Some((key, value)) = parse_header(&tokens[i + 1]) {
-                                            headers.insert(key.to_lowercase(), value);
-                                        }
-                                        i += 1;
+                                'H' if i + 1 < tokens.len() => {
+                                    if let Some((key, value)) = parse_header(&tokens[i + 1]) {
+                                        headers.insert(key.to_lowercase(), value);
                                     }
+                                    i += 1;
                                 }
-                                'd' => {
-                                    if i + 1 < tokens.len() {
-                                        data = Some(tokens[i + 1].clone());
-                                        i += 1;
-                                    }
+                                'd' if i + 1 < tokens.len() => {
+                                    data = Some(tokens[i + 1].clone());
+                                    i += 1;
                                 }
+                                'G' => get_data = true,
                                 'v' | 'i' | 's' | 'k' | 'L' | 'f' => {}
                                 _ => {}
                             }
@@ -246,7 +285,12 @@ impl HttpTool {
             return Err("URL not found in curl command".to_string());
         }
 
-        if let Some(data_str) = data {
+        if get_data {
+            // -G 时 -d 的数据作为查询参数
+            if let Some(data_str) = data {
+                tool.params = parse_query(&data_str);
+            }
+        } else if let Some(data_str) = data {
             tool.body = data_str.into_bytes();
         } else if !form_data.is_empty() {
             let mut body_parts = Vec::new();
@@ -271,20 +315,21 @@ impl HttpTool {
         Ok(tool)
     }
 
+    #[cfg(feature = "godot")]
     pub fn send_request(&self, request: &mut HttpRequest) -> Result<(), godot::global::Error> {
         if self.url.is_empty() {
             godot_error!("HttpTool: URL is empty.");
             return Err(godot::global::Error::ERR_INVALID_PARAMETER);
         }
         let mut headers = PackedStringArray::new();
-        for (key, value) in &self.headers {
-            headers.push(format!("{}: {}", key, value).as_str());
+        for line in self.header_lines(crate::time::get_current_time()) {
+            headers.push(line.as_str());
         }
 
         let body = PackedByteArray::from(self.body.clone());
 
         let err = request
-            .request_raw_ex(self.url.as_str())
+            .request_raw_ex(self.full_url().as_str())
             .custom_headers(&headers)
             .method(http_client::Method::from(self.method.clone()))
             .request_data_raw(&body)
@@ -424,4 +469,51 @@ mod http_test {
         assert_eq!(tool.headers.get("origin"), Some(&"https://weav3r.dev".to_string()));
         assert_eq!(tool.headers.get("cookie"), Some(&"test=value".to_string()));
     }
+
+    #[test]
+    fn test_cookie_header_from_jar() {
+        let mut tool = HttpTool::default();
+        tool.add_header("Cookie", "stale=1");
+        assert_eq!(tool.header_lines(0), vec!["Cookie: stale=1"]);
+        tool.set_cookies(CookieJar::parse("a=1; b=2"));
+        assert_eq!(tool.header_lines(0), vec!["Cookie: a=1; b=2"]);
+    }
+
+    #[test]
+    fn test_full_url() {
+        let mut tool = HttpTool::default();
+        tool.set_url("https://api.torn.com/v2/user/log?striptags=true");
+        tool.add_param("limit", "100");
+        tool.add_param("cat", "a b");
+        tool.add_param("cat", "c&d");
+        let url = tool.full_url();
+        assert_eq!(
+            url,
+            "https://api.torn.com/v2/user/log?striptags=true&limit=100&cat=a%20b&cat=c%26d"
+        );
+
+        // 生成的地址经过 curl 解析后保持不变
+        let tool = HttpTool::from_curl(&format!("curl '{}'", url)).unwrap();
+        assert_eq!(tool.method, HttpMethod::GET);
+        assert_eq!(tool.full_url(), url);
+        let (_, query) = tool.url.split_once('?').unwrap();
+        assert_eq!(
+            parse_query(query),
+            vec![
+                ("striptags".to_string(), "true".to_string()),
+                ("limit".to_string(), "100".to_string()),
+                ("cat".to_string(), "a b".to_string()),
+                ("cat".to_string(), "c&d".to_string()),
+            ]
+        );
+
+        // -G 时数据作为查询参数
+        let tool = HttpTool::from_curl(
+            "curl -G 'https://api.torn.com/v2/user/log?striptags=true' -d 'limit=100&cat=a%20b&cat=c%26d'",
+        )
+        .unwrap();
+        assert_eq!(tool.method, HttpMethod::GET);
+        assert!(tool.body.is_empty());
+        assert_eq!(tool.full_url(), url);
+    }
 }
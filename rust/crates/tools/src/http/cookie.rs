@@ -0,0 +1,250 @@
+/// 单个 cookie，expires 为过期时间戳，None 表示会话 cookie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub expires: Option<u64>,
+}
+
+/// cookie 集合，只用于单个站点，不区分 Domain 和 Path
+/// 保持加入的顺序，生成的 Cookie 头与原始字符串顺序一致
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// 解析 Cookie 头格式的字符串：`a=1; b=2`
+    pub fn parse(text: &str) -> Self {
+        let mut jar = Self::default();
+        for pair in text.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            jar.set(Cookie {
+                name: name.to_string(),
+                value: value.trim().to_string(),
+                expires: None,
+            });
+        }
+        jar
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| x.value.as_str())
+    }
+
+    /// 添加或替换同名 cookie，替换时保持原来的位置
+    pub fn set(&mut self, cookie: Cookie) {
+        match self.cookies.iter_mut().find(|x| x.name == cookie.name) {
+            Some(old) => *old = cookie,
+            None => self.cookies.push(cookie),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.cookies.len();
+        self.cookies.retain(|x| x.name != name);
+        self.cookies.len() != len
+    }
+
+    /// 应用一个 Set-Cookie 值，Max-Age 优先于 Expires，已经过期时删除
+    /// 只有值变化、新增或删除时返回 true，只更新过期时间不算变化
+    pub fn apply_set_cookie(&mut self, header: &str, now: u64) -> bool {
+        let mut parts = header.split(';');
+        let Some((name, value)) = parts.next().and_then(|x| x.split_once('=')) else {
+            return false;
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return false;
+        }
+        let mut max_age: Option<i64> = None;
+        let mut expires: Option<u64> = None;
+        for attr in parts {
+            let (key, attr_value) = attr.split_once('=').unwrap_or((attr, ""));
+            let key = key.trim();
+            if key.eq_ignore_ascii_case("max-age") {
+                max_age = attr_value.trim().parse::<i64>().ok();
+            } else if key.eq_ignore_ascii_case("expires") {
+                expires = parse_http_date(attr_value.trim());
+            }
+        }
+        let expires = match max_age {
+            Some(sec) if sec <= 0 => Some(0),
+            Some(sec) => Some(now.saturating_add(sec as u64)),
+            None => expires,
+        };
+        if expires.is_some_and(|x| x <= now) {
+            return self.remove(name);
+        }
+        let value = value.trim();
+        if let Some(old) = self.cookies.iter_mut().find(|x| x.name == name) {
+            let changed = old.value != value;
+            old.value = value.to_string();
+            old.expires = expires;
+            return changed;
+        }
+        self.cookies.push(Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            expires,
+        });
+        true
+    }
+
+    /// 应用响应头中所有的 Set-Cookie，返回是否有变化
+    pub fn apply_headers(&mut self, headers: &[String], now: u64) -> bool {
+        let mut changed = false;
+        for header in headers {
+            let Some((key, value)) = header.split_once(':') else {
+                continue;
+            };
+            if key.trim().eq_ignore_ascii_case("set-cookie") {
+                changed |= self.apply_set_cookie(value, now);
+            }
+        }
+        changed
+    }
+
+    /// 生成 Cookie 头，跳过已经过期的 cookie
+    pub fn to_header(&self, now: u64) -> String {
+        self.cookies
+            .iter()
+            .filter(|x| x.expires.is_none_or(|expires| expires > now))
+            .map(|x| format!("{}={}", x.name, x.value))
+            .collect::<Vec<String>>()
+            .join("; ")
+    }
+}
+
+/// 解析 HTTP 日期：`Wed, 21 Oct 2015 07:28:00 GMT`，也支持 `21-Oct-2015` 和两位年份
+fn parse_http_date(text: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let (mut day, mut month, mut year, mut time) = (None, None, None, None);
+    for token in text.split([' ', ',', '-']).filter(|x| !x.is_empty()) {
+        if token.contains(':') {
+            let mut parts = token.split(':').map(|x| x.parse::<u64>().ok());
+            time = Some((parts.next()??, parts.next()??, parts.next()??));
+        } else if let Ok(n) = token.parse::<i64>() {
+            if token.len() <= 2 && day.is_none() {
+                day = Some(n);
+            } else {
+                year = Some(match n {
+                    0..=69 => n + 2000,
+                    70..=99 => n + 1900,
+                    _ => n,
+                });
+            }
+        } else if let Some(i) = MONTHS
+            .iter()
+            .position(|x| token.to_lowercase().starts_with(x))
+        {
+            month = Some(i as i64 + 1);
+        }
+    }
+    let (day, month, year) = (day?, month?, year?);
+    let (h, m, s) = time.unwrap_or_default();
+    if !(1..=31).contains(&day) || h > 23 || m > 59 || s > 60 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    u64::try_from(days * 86400)
+        .ok()
+        .map(|x| x + h * 3600 + m * 60 + s)
+}
+
+/// 公历日期到 1970-01-01 的天数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod cookie_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_header() {
+        let mut jar = CookieJar::parse("_ga=GA1.1.5; cf_clearance=abc-1.2=3; ;bad");
+        assert_eq!(jar.get("cf_clearance"), Some("abc-1.2=3"));
+        assert_eq!(jar.to_header(0), "_ga=GA1.1.5; cf_clearance=abc-1.2=3");
+        jar.set(Cookie {
+            name: "_ga".to_string(),
+            value: "new".to_string(),
+            expires: None,
+        });
+        assert_eq!(jar.to_header(0), "_ga=new; cf_clearance=abc-1.2=3");
+    }
+
+    #[test]
+    fn test_set_cookie() {
+        let now = 1_700_000_000;
+        let mut jar = CookieJar::parse("a=1; cf_clearance=old");
+        let headers = vec![
+            "Content-Type: text/x-component".to_string(),
+            "Set-Cookie: cf_clearance=new; Path=/; Max-Age=60; HttpOnly".to_string(),
+            "set-cookie: b=2; Expires=Wed, 21 Oct 2065 07:28:00 GMT".to_string(),
+        ];
+        assert!(jar.apply_headers(&headers, now));
+        assert_eq!(jar.to_header(now), "a=1; cf_clearance=new; b=2");
+        // 同样的值不算变化
+        assert!(!jar.apply_headers(&headers, now));
+        // Max-Age 到期后不再发送
+        assert_eq!(jar.to_header(now + 60), "a=1; b=2");
+
+        // Max-Age=0 和过去的时间都表示删除
+        assert!(jar.apply_set_cookie("a=; Max-Age=0", now));
+        assert!(jar.apply_set_cookie("b=deleted; expires=Thu, 01-Jan-1970 00:00:01 GMT", now));
+        assert_eq!(jar.to_header(now), "cf_clearance=new");
+        assert!(!jar.apply_set_cookie("missing=; Max-Age=0", now));
+    }
+
+    #[test]
+    fn test_set_cookie_expires_only() {
+        let now = 1_700_000_000;
+        let mut jar = CookieJar::parse("a=0");
+        assert!(jar.apply_set_cookie("a=1; Max-Age=3600", now));
+        assert!(!jar.apply_set_cookie("a=1; Max-Age=3600", now + 10));
+        // 从保存的字符串重新解析后没有过期时间，同样的值也不算变化
+        let mut jar = CookieJar::parse(&jar.to_header(now));
+        assert!(!jar.apply_set_cookie("a=1; Max-Age=3600", now));
+        // 过期时间仍然更新
+        assert_eq!(jar.to_header(now + 3600), "");
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(1445412480)
+        );
+        assert_eq!(
+            parse_http_date("Wednesday, 21-Oct-15 07:28:00 GMT"),
+            Some(1445412480)
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(1709164800)
+        );
+        assert_eq!(parse_http_date("not a date"), None);
+    }
+}
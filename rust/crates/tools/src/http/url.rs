@@ -0,0 +1,125 @@
+/// 查询参数编码，除 RFC 3986 非保留字符外都编码为 `%XX`
+pub fn percent_encode(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                res.push(b as char)
+            }
+            _ => res.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    res
+}
+
+/// 查询参数解码，`+` 解码为空格，不完整的 `%` 保留原文
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|x| u8::from_str_radix(x, 16).ok()) {
+                    Some(b) => {
+                        res.push(b);
+                        i += 3;
+                        continue;
+                    }
+                    None => res.push(b'%'),
+                }
+            }
+            b'+' => res.push(b' '),
+            b => res.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+/// 解析查询字符串 `a=1&b=2&a=3`，保持原来的顺序和重复的参数
+pub fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|x| !x.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// 生成查询字符串，按给定的顺序编码
+pub fn encode_query(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// 把参数追加到地址上，地址中已有的查询字符串保持原样放在前面，`#` 后的片段保留在最后
+pub fn build_url(url: &str, params: &[(String, String)]) -> String {
+    let (url, fragment) = match url.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (url, None),
+    };
+    let (base, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = query.trim_matches('&');
+    let params = encode_query(params);
+    let query = match (query.is_empty(), params.is_empty()) {
+        (true, _) => params,
+        (false, true) => query.to_string(),
+        (false, false) => format!("{}&{}", query, params),
+    };
+    let mut res = base.to_string();
+    if !query.is_empty() {
+        res.push('?');
+        res.push_str(&query);
+    }
+    if let Some(fragment) = fragment {
+        res.push('#');
+        res.push_str(fragment);
+    }
+    res
+}
+
+#[cfg(test)]
+mod url_test {
+    use super::*;
+
+    fn params(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("a-b_c.d~1"), "a-b_c.d~1");
+        assert_eq!(percent_encode("a b&c=d/é"), "a%20b%26c%3Dd%2F%C3%A9");
+        assert_eq!(percent_decode("a%20b%26c%3Dd%2F%C3%A9"), "a b&c=d/é");
+        assert_eq!(percent_decode("a+b%2"), "a b%2");
+        assert_eq!(percent_decode("%zz%"), "%zz%");
+    }
+
+    #[test]
+    fn test_build_url() {
+        let list = params(&[("limit", "100"), ("cat", "1 2"), ("cat", "3")]);
+        assert_eq!(
+            build_url("https://api.torn.com/v2/user/log", &list),
+            "https://api.torn.com/v2/user/log?limit=100&cat=1%202&cat=3"
+        );
+        assert_eq!(
+            build_url("https://a.com/p?x=1&#top", &params(&[("y", "&")])),
+            "https://a.com/p?x=1&y=%26#top"
+        );
+        assert_eq!(build_url("https://a.com/p?x=1", &[]), "https://a.com/p?x=1");
+        assert_eq!(build_url("https://a.com/p?", &[]), "https://a.com/p");
+        assert_eq!(
+            parse_query("limit=100&cat=1%202&cat=3&flag"),
+            params(&[("limit", "100"), ("cat", "1 2"), ("cat", "3"), ("flag", "")])
+        );
+    }
+}
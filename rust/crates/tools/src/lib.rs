@@ -1,8 +1,15 @@
-pub mod cfg;
-pub mod node;
 pub mod time;
 pub mod http;
 pub mod base;
+pub mod order_change;
+
+#[cfg(feature = "godot")]
+pub mod cfg;
+#[cfg(feature = "godot")]
+pub mod node;
+#[cfg(feature = "godot")]
 pub mod file;
+#[cfg(feature = "godot")]
 pub mod image;
-pub mod order_change;
\ No newline at end of file
+#[cfg(feature = "godot")]
+pub mod logger;
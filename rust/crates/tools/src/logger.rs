@@ -0,0 +1,33 @@
+use godot::global::{godot_error, godot_print, godot_warn};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// 将 log 日志转发到 Godot 输出面板
+pub struct GodotLogger;
+
+static LOGGER: GodotLogger = GodotLogger;
+
+impl Log for GodotLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Error => godot_error!("{}", record.args()),
+            Level::Warn => godot_warn!("{}", record.args()),
+            _ => godot_print!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// 注册 Godot 日志，重复调用无影响
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
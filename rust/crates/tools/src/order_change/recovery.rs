@@ -164,9 +164,9 @@ pub struct ErrorHandler;
 impl ErrorHandler {
     pub fn log_error(error: &OrderChangeError, context: &str) {
         if error.is_critical() {
-            godot::global::godot_error!("{}: Critical error: {}", context, error);
+            log::error!("{}: Critical error: {}", context, error);
         } else {
-            godot::global::godot_warn!("{}: Recoverable error: {}", context, error);
+            log::warn!("{}: Recoverable error: {}", context, error);
         }
     }
 
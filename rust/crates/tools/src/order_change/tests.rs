@@ -222,6 +222,7 @@ fn test_content_hashable_string() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_content_hashable_primitives() {
     assert_eq!(42i32.content_hash(), 42i32.content_hash());
     assert_ne!(42i32.content_hash(), 43i32.content_hash());
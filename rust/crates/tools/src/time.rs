@@ -5,4 +5,26 @@ pub fn get_current_time() -> u64 {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
-}
\ No newline at end of file
+}
+
+/// 秒数转换为可读时长，例如 `1h2m3s`
+pub fn format_duration(sec: u64) -> String {
+    let (h, m, s) = (sec / 3600, sec % 3600 / 60, sec % 60);
+    match (h, m) {
+        (0, 0) => format!("{}s", s),
+        (0, _) => format!("{}m{}s", m, s),
+        _ => format!("{}h{}m{}s", h, m, s),
+    }
+}
+
+#[cfg(test)]
+mod time_test {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(5), "5s");
+        assert_eq!(format_duration(65), "1m5s");
+        assert_eq!(format_duration(3725), "1h2m5s");
+    }
+}
@@ -1,67 +1,323 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{Arc, RwLock},
+};
 
 use csv::ReaderBuilder;
-use godot::{
-    classes::{FileAccess, file_access::ModeFlags},
-    global::godot_error,
+use model::{
+    error::MyError,
+    items::{CsvItemInfo, ItemInfo},
 };
-use model::items::{CsvItemInfo, ItemInfo};
+use serde::{Deserialize, Serialize};
 
-static OFFICE_ITEM_INFO_LIST: OnceLock<Vec<ItemInfo>> = OnceLock::new();
-static OFFICE_ITEM_INFO_MAP: OnceLock<HashMap<i32, ItemInfo>> = OnceLock::new();
-static WEAPON_ITEM_INFO_MAP: OnceLock<HashMap<i32, ItemInfo>> = OnceLock::new();
+/// 当前使用的物品目录，刷新时整体替换
+static ITEM_CATALOG: RwLock<Option<Arc<ItemCatalog>>> = RwLock::new(None);
 
-/// 获取物品列表
-pub fn get_item_list() -> &'static Vec<ItemInfo> {
-    OFFICE_ITEM_INFO_LIST.get_or_init(|| {
-        let Some(mut file) = FileAccess::open("res://assets/data/torncity_items.csv", ModeFlags::READ)
-        else {
-            godot_error!("Failed to open torncity_items.csv");
-            return Vec::new();
+/// 内置物品列表路径
+pub const ITEM_CSV_PATH: &str = "res://assets/data/torncity_items.csv";
+/// 从 Torn API 拉取的物品列表保存路径
+pub const ITEM_CACHE_PATH: &str = "user://torn_items.json";
+
+/// 从 csv 内容中解析物品列表，解析失败的行会被跳过
+pub fn parse_item_list<R: Read>(reader: R) -> Vec<ItemInfo> {
+    let mut reader = ReaderBuilder::new().has_headers(true).from_reader(reader);
+
+    reader
+        .deserialize::<CsvItemInfo>()
+        .filter_map(|result| match result {
+            Ok(item) => Some(ItemInfo::from(item)),
+            Err(e) => {
+                log::error!("Failed to parse CSV row: {:?}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 装备类型，价值取决于品质和加成，不能按普通物品的市场均价估算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EquipmentKind {
+    Weapon,
+    Armor,
+}
+
+impl EquipmentKind {
+    /// 武器是指 weapon_type 不为空的物品（排除 Temporary 类型）
+    /// 防具按类型判断，内置 csv 为 Defensive，Torn API v2 为 Armor
+    pub fn of(item: &ItemInfo) -> Option<Self> {
+        if !item.weapon_type.is_empty() && item.weapon_type != "Temporary" {
+            return Some(EquipmentKind::Weapon);
+        }
+        match item.t.as_str() {
+            "Defensive" | "Armor" => Some(EquipmentKind::Armor),
+            _ => None,
+        }
+    }
+}
+
+/// 物品目录来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogSource {
+    /// 内置 csv
+    Bundled,
+    /// Torn API
+    TornApi,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ItemCatalogFile {
+    version: u32,
+    fetched_at: u64,
+    items: Vec<ItemInfo>,
+}
+
+/// 物品目录，包含物品列表和按 id 索引的 map
+#[derive(Debug, Clone)]
+pub struct ItemCatalog {
+    pub source: CatalogSource,
+    /// 拉取时间，内置 csv 为 0
+    pub fetched_at: u64,
+    items: Arc<Vec<ItemInfo>>,
+    item_map: Arc<HashMap<i32, ItemInfo>>,
+    weapon_map: Arc<HashMap<i32, ItemInfo>>,
+}
+
+impl ItemCatalog {
+    pub const FILE_VERSION: u32 = 1;
+
+    pub fn new(items: Vec<ItemInfo>, source: CatalogSource, fetched_at: u64) -> Self {
+        let item_map = items.iter().map(|item| (item.id, item.clone())).collect();
+        let weapon_map = items
+            .iter()
+            .filter(|item| EquipmentKind::of(item) == Some(EquipmentKind::Weapon))
+            .map(|item| (item.id, item.clone()))
+            .collect();
+        Self {
+            source,
+            fetched_at,
+            items: Arc::new(items),
+            item_map: Arc::new(item_map),
+            weapon_map: Arc::new(weapon_map),
+        }
+    }
+
+    pub fn items(&self) -> Arc<Vec<ItemInfo>> {
+        self.items.clone()
+    }
+
+    pub fn item_map(&self) -> Arc<HashMap<i32, ItemInfo>> {
+        self.item_map.clone()
+    }
+
+    pub fn weapon_map(&self) -> Arc<HashMap<i32, ItemInfo>> {
+        self.weapon_map.clone()
+    }
+
+    pub fn to_json(&self) -> Result<String, MyError> {
+        let file = ItemCatalogFile {
+            version: Self::FILE_VERSION,
+            fetched_at: self.fetched_at,
+            items: self.items.to_vec(),
         };
+        serde_json::to_string(&file).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+
+    /// 读取保存的目录，版本不一致或没有物品时返回错误
+    pub fn from_json(json: &str) -> Result<Self, MyError> {
+        let file: ItemCatalogFile =
+            serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))?;
+        if file.version != Self::FILE_VERSION {
+            return Err(MyError::JsonParse(format!(
+                "item catalog version {} is not supported",
+                file.version
+            )));
+        }
+        if file.items.is_empty() {
+            return Err(MyError::JsonParse("item catalog is empty".to_string()));
+        }
+        Ok(Self::new(
+            file.items,
+            CatalogSource::TornApi,
+            file.fetched_at,
+        ))
+    }
+}
+
+/// 使用指定数据源初始化物品列表，需要在第一次获取物品列表前调用
+/// 已经初始化过则返回 false
+pub fn init_item_list<R: Read>(reader: R) -> bool {
+    let mut catalog = ITEM_CATALOG.write().unwrap_or_else(|e| e.into_inner());
+    if catalog.is_some() {
+        return false;
+    }
+    *catalog = Some(Arc::new(ItemCatalog::new(
+        parse_item_list(reader),
+        CatalogSource::Bundled,
+        0,
+    )));
+    true
+}
+
+/// 替换当前的物品目录，已经获取的旧目录不受影响
+pub fn set_catalog(catalog: ItemCatalog) {
+    *ITEM_CATALOG.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(catalog));
+}
 
+/// 获取当前的物品目录，第一次获取时加载保存的目录，没有时使用内置 csv
+pub fn get_catalog() -> Arc<ItemCatalog> {
+    if let Some(catalog) = ITEM_CATALOG
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return catalog.clone();
+    }
+    let mut catalog = ITEM_CATALOG.write().unwrap_or_else(|e| e.into_inner());
+    catalog
+        .get_or_insert_with(|| Arc::new(load_default_catalog()))
+        .clone()
+}
+
+/// 获取物品列表
+pub fn get_item_list() -> Arc<Vec<ItemInfo>> {
+    get_catalog().items()
+}
+
+/// 读取保存的目录，没有或无法解析时读取内置的物品列表
+#[cfg(feature = "godot")]
+fn load_default_catalog() -> ItemCatalog {
+    use godot::classes::{FileAccess, file_access::ModeFlags};
+
+    let read_text = |path: &str| {
+        let mut file = FileAccess::open(path, ModeFlags::READ)?;
         // 读取全部内容
         let length = file.get_length() as i64;
         let buffer = file.get_buffer(length);
         let buffer = buffer.to_vec();
-        let content = String::from_utf8_lossy(&buffer);
-
-        // 解析 CSV
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(content.as_bytes());
-
-        reader
-            .deserialize::<CsvItemInfo>()
-            .filter_map(|result| match result {
-                Ok(item) => Some(ItemInfo::from(item)),
-                Err(e) => {
-                    godot_error!("Failed to parse CSV row: {:?}", e);
-                    None
-                }
-            })
-            .collect()
-    })
+        Some(String::from_utf8_lossy(&buffer).to_string())
+    };
+
+    if FileAccess::file_exists(ITEM_CACHE_PATH)
+        && let Some(json) = read_text(ITEM_CACHE_PATH)
+    {
+        match ItemCatalog::from_json(&json) {
+            Ok(catalog) => return catalog,
+            Err(e) => log::error!("Failed to load {}: {:?}", ITEM_CACHE_PATH, e),
+        }
+    }
+
+    let Some(content) = read_text(ITEM_CSV_PATH) else {
+        log::error!("Failed to open torncity_items.csv");
+        return ItemCatalog::new(Vec::new(), CatalogSource::Bundled, 0);
+    };
+    ItemCatalog::new(
+        parse_item_list(content.as_bytes()),
+        CatalogSource::Bundled,
+        0,
+    )
+}
+
+/// 非 Godot 环境没有内置资源，需要通过 init_item_list 初始化
+#[cfg(not(feature = "godot"))]
+fn load_default_catalog() -> ItemCatalog {
+    log::warn!("Item list is not initialized, call init_item_list first");
+    ItemCatalog::new(Vec::new(), CatalogSource::Bundled, 0)
 }
 
 /// 获取物品 id map
-pub fn get_item_info_map() -> &'static HashMap<i32, ItemInfo> {
-    OFFICE_ITEM_INFO_MAP.get_or_init(|| {
-        get_item_list()
-            .iter()
-            .map(|item| (item.id, item.clone()))
-            .collect()
-    })
+pub fn get_item_info_map() -> Arc<HashMap<i32, ItemInfo>> {
+    get_catalog().item_map()
 }
 
 /// 获取武器物品 id map
 /// 武器是指 weapon_type 不为空的物品（排除 Temporary 类型）
-pub fn weapon_id_map() -> &'static HashMap<i32, ItemInfo> {
-    WEAPON_ITEM_INFO_MAP.get_or_init(|| {
-        get_item_list()
-            .iter()
-            .filter(|item| !item.weapon_type.is_empty() && item.weapon_type != "Temporary")
-            .map(|item| (item.id, item.clone()))
-            .collect()
-    })
+pub fn weapon_id_map() -> Arc<HashMap<i32, ItemInfo>> {
+    get_catalog().weapon_map()
+}
+
+#[cfg(test)]
+mod item_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_item_list() {
+        let csv = "id,name,description,effect,requirement,type,weapon_type,buy_price,sell_price,market_value,circulation,image,tradeable\n\
+            1,Hammer,A small tool,,,Melee,Clubbing,75,50,28,2881169,https://www.torn.com/images/items/1/large.png,TRUE\n\
+            bad,row\n\
+            186,Sheep Plushie,Baaaaaa.,,,Plushie,,25,15,631,67788555,https://www.torn.com/images/items/186/large.png,FALSE\n";
+        let items = parse_item_list(csv.as_bytes());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].weapon_type, "Clubbing");
+        assert!(items[0].tradeable);
+        assert_eq!(items[1].t, "Plushie");
+        assert!(!items[1].tradeable);
+    }
+
+    #[test]
+    fn test_parse_bundled_item_list() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../godot/assets/data/torncity_items.csv"
+        );
+        let file = std::fs::File::open(path).unwrap();
+        let items = parse_item_list(file);
+        assert!(items.iter().any(|x| x.id == 206));
+    }
+
+    #[test]
+    fn test_catalog_swap() {
+        let item = |id: i32, name: &str, weapon_type: &str| ItemInfo {
+            id,
+            name: name.to_string(),
+            weapon_type: weapon_type.to_string(),
+            ..Default::default()
+        };
+        let catalog = ItemCatalog::new(
+            vec![item(1, "Hammer", "Clubbing"), item(206, "Xanax", "")],
+            CatalogSource::TornApi,
+            100,
+        );
+        let restored = ItemCatalog::from_json(&catalog.to_json().unwrap()).unwrap();
+        assert_eq!(restored.fetched_at, 100);
+        assert_eq!(restored.items().len(), 2);
+        assert!(ItemCatalog::from_json(r#"{"version":0,"fetched_at":0,"items":[]}"#).is_err());
+
+        // 替换后查询立即使用新的数据，旧的引用不受影响
+        set_catalog(catalog);
+        let old = get_item_info_map();
+        assert_eq!(weapon_id_map().len(), 1);
+        set_catalog(ItemCatalog::new(
+            vec![item(206, "Xanax (new)", "")],
+            CatalogSource::TornApi,
+            200,
+        ));
+        assert_eq!(get_item_info_map()[&206].name, "Xanax (new)");
+        assert!(weapon_id_map().is_empty());
+        assert_eq!(old[&206].name, "Xanax");
+        assert!(!init_item_list("".as_bytes()));
+    }
+
+    #[test]
+    fn test_equipment_kind() {
+        let item = |t: &str, weapon_type: &str| ItemInfo {
+            t: t.to_string(),
+            weapon_type: weapon_type.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            EquipmentKind::of(&item("Melee", "Clubbing")),
+            Some(EquipmentKind::Weapon)
+        );
+        assert_eq!(EquipmentKind::of(&item("Temporary", "Temporary")), None);
+        assert_eq!(
+            EquipmentKind::of(&item("Defensive", "")),
+            Some(EquipmentKind::Armor)
+        );
+        assert_eq!(
+            EquipmentKind::of(&item("Armor", "")),
+            Some(EquipmentKind::Armor)
+        );
+        assert_eq!(EquipmentKind::of(&item("Drug", "")), None);
+    }
 }
@@ -0,0 +1,438 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use model::user_log::UserLogEntry;
+
+use crate::trade::{Trade, TradeStatus, reconstruct_trades};
+
+// 买入
+pub const TORN_LOG_BAZAAR_BUY: u32 = 1225;
+pub const TORN_LOG_ITEM_MARKET_BUY: u32 = 1112;
+// 卖出
+pub const TORN_LOG_BAZAAR_SELL: u32 = 1221;
+pub const TORN_LOG_ITEM_MARKET_SELL: u32 = 1113;
+pub const TORN_LOG_SHOP_SELL: u32 = 4210;
+
+const DAY_SEC: i64 = 86400;
+
+/// 买卖渠道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LedgerChannel {
+    Bazaar,
+    ItemMarket,
+    /// NPC 商店
+    Shop,
+    Trade,
+}
+
+/// 买入还是卖出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerSide {
+    Buy,
+    Sell,
+}
+
+/// 一次买入或卖出，一条日志中的多个物品会拆成多个事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerEvent {
+    /// 来源日志 id，用于去重
+    pub log_id: String,
+    pub timestamp: i64,
+    pub side: LedgerSide,
+    pub channel: LedgerChannel,
+    pub item_id: i32,
+    pub quantity: u64,
+    /// 单价
+    pub price_each: u64,
+    /// 总费用
+    pub fee: u64,
+    /// 买入时为卖家，卖出时为买家
+    pub counterparty: Option<i64>,
+}
+
+impl LedgerEvent {
+    /// 从用户日志解析，不是买卖日志时返回空
+    pub fn from_log(log: &UserLogEntry) -> Vec<LedgerEvent> {
+        let (side, channel) = match log.details.id {
+            TORN_LOG_BAZAAR_BUY => (LedgerSide::Buy, LedgerChannel::Bazaar),
+            TORN_LOG_ITEM_MARKET_BUY => (LedgerSide::Buy, LedgerChannel::ItemMarket),
+            TORN_LOG_BAZAAR_SELL => (LedgerSide::Sell, LedgerChannel::Bazaar),
+            TORN_LOG_ITEM_MARKET_SELL => (LedgerSide::Sell, LedgerChannel::ItemMarket),
+            TORN_LOG_SHOP_SELL => (LedgerSide::Sell, LedgerChannel::Shop),
+            _ => return Vec::new(),
+        };
+        let data = &log.data;
+        let total_quantity = data.items.iter().map(|x| x.qty).sum::<u64>().max(1);
+        let price_each = match (data.cost_each, data.cost_total.or(data.total_value)) {
+            (Some(each), _) => each,
+            (None, Some(total)) => total / total_quantity,
+            (None, None) => {
+                log::warn!("LedgerEvent: log {} has no price", log.id);
+                return Vec::new();
+            }
+        };
+        let counterparty = match side {
+            LedgerSide::Buy => data.seller,
+            LedgerSide::Sell => data.buyer,
+        };
+        let fee = data.fee.unwrap_or_default();
+        data.items
+            .iter()
+            .map(|item| LedgerEvent {
+                log_id: log.id.clone(),
+                timestamp: log.timestamp,
+                side,
+                channel,
+                item_id: item.id,
+                quantity: item.qty,
+                price_each,
+                // 多个物品时按数量分摊费用
+                fee: fee * item.qty / total_quantity,
+                counterparty,
+            })
+            .collect()
+    }
+
+    /// 从完成的交易解析，交易日志只有双方放入的金钱和物品：
+    /// 自己放入物品、对方放入金钱视为卖出，反过来视为买入，金钱按数量分摊为单价
+    /// 不能整除时余数分给前面的物品，单价加 1，同一物品拆成两个单价
+    /// 没有金钱、没有物品或者双方都放入物品的交易无法确定价格，返回空
+    pub fn from_trade(trade: &Trade) -> Vec<LedgerEvent> {
+        if trade.status != TradeStatus::Accepted {
+            return Vec::new();
+        }
+        let (side, items, money) =
+            match (trade.ours.items.is_empty(), trade.theirs.items.is_empty()) {
+                (false, true) => (LedgerSide::Sell, &trade.ours.items, trade.theirs.money),
+                (true, false) => (LedgerSide::Buy, &trade.theirs.items, trade.ours.money),
+                (true, true) => return Vec::new(),
+                (false, false) => {
+                    log::warn!(
+                        "LedgerEvent: trade with {} has items on both sides",
+                        trade.user
+                    );
+                    return Vec::new();
+                }
+            };
+        if money == 0 || items.values().any(|x| *x <= 0) {
+            return Vec::new();
+        }
+        let Some(log_id) = trade.log_ids.last() else {
+            return Vec::new();
+        };
+        let total_quantity = items.values().map(|x| x.unsigned_abs()).sum::<u64>();
+        let price_each = money / total_quantity;
+        let mut extra = money % total_quantity;
+        let mut res = Vec::new();
+        for (item_id, qty) in items.iter() {
+            let qty = qty.unsigned_abs();
+            let high = qty.min(extra);
+            extra -= high;
+            for (quantity, price_each) in [(high, price_each + 1), (qty - high, price_each)] {
+                if quantity == 0 {
+                    continue;
+                }
+                res.push(LedgerEvent {
+                    log_id: log_id.clone(),
+                    timestamp: trade.updated_at,
+                    side,
+                    channel: LedgerChannel::Trade,
+                    item_id: *item_id,
+                    quantity,
+                    price_each,
+                    fee: 0,
+                    counterparty: Some(trade.user),
+                });
+            }
+        }
+        res
+    }
+}
+
+/// 持仓批次
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    pub item_id: i32,
+    pub quantity: u64,
+    pub cost_each: u64,
+    pub bought_at: i64,
+    pub channel: LedgerChannel,
+    pub seller: Option<i64>,
+}
+
+/// 已实现的利润，一次卖出匹配到多个批次时拆成多条
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealizedSale {
+    pub item_id: i32,
+    pub quantity: u64,
+    pub cost_each: u64,
+    pub sell_each: u64,
+    /// 按数量分摊的费用
+    pub fee: u64,
+    pub bought_at: i64,
+    pub sold_at: i64,
+    /// 买入批次的卖家
+    pub seller: Option<i64>,
+    pub channel: LedgerChannel,
+}
+
+impl RealizedSale {
+    pub fn profit(&self) -> i64 {
+        (self.sell_each as i64 - self.cost_each as i64) * self.quantity as i64 - self.fee as i64
+    }
+}
+
+/// 已实现盈亏账本，卖出按先进先出匹配买入批次
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    lots: HashMap<i32, VecDeque<Lot>>,
+    realized: Vec<RealizedSale>,
+    /// 没有买入记录的卖出数量，无法计算成本
+    unmatched: Vec<LedgerEvent>,
+    seen: HashSet<(String, i32, u64)>,
+}
+
+impl Ledger {
+    /// 从用户日志构建账本，日志可以是任意顺序
+    pub fn from_logs(logs: &[UserLogEntry]) -> Self {
+        let mut ledger = Self::default();
+        ledger.add_logs(logs);
+        ledger
+    }
+
+    /// 追加日志，需要保证新日志不早于已经处理过的日志
+    /// 交易由同一批日志重建，跨批次的交易需要一次传入全部日志
+    pub fn add_logs(&mut self, logs: &[UserLogEntry]) {
+        let mut events: Vec<LedgerEvent> = logs.iter().flat_map(LedgerEvent::from_log).collect();
+        events.extend(
+            reconstruct_trades(logs)
+                .iter()
+                .flat_map(LedgerEvent::from_trade),
+        );
+        self.add_events(&mut events);
+    }
+
+    /// 追加事件，按时间排序，同一时间先处理买入
+    pub fn add_events(&mut self, events: &mut [LedgerEvent]) {
+        events.sort_by_key(|x| (x.timestamp, x.side == LedgerSide::Sell, x.log_id.clone()));
+        for event in events.iter() {
+            self.add_event(event.clone());
+        }
+    }
+
+    /// 处理单个事件，同一日志同一物品同一单价只处理一次
+    pub fn add_event(&mut self, event: LedgerEvent) {
+        if !self
+            .seen
+            .insert((event.log_id.clone(), event.item_id, event.price_each))
+        {
+            return;
+        }
+        match event.side {
+            LedgerSide::Buy => self.lots.entry(event.item_id).or_default().push_back(Lot {
+                item_id: event.item_id,
+                quantity: event.quantity,
+                cost_each: event.price_each,
+                bought_at: event.timestamp,
+                channel: event.channel,
+                seller: event.counterparty,
+            }),
+            LedgerSide::Sell => self.sell(event),
+        }
+    }
+
+    fn sell(&mut self, event: LedgerEvent) {
+        let lots = self.lots.entry(event.item_id).or_default();
+        let mut remaining = event.quantity;
+        let mut fee_left = event.fee;
+        while remaining > 0 {
+            let Some(lot) = lots.front_mut() else {
+                break;
+            };
+            let quantity = remaining.min(lot.quantity);
+            // 最后一段承担剩余的费用，避免取整误差
+            let fee = if quantity == remaining {
+                fee_left
+            } else {
+                event.fee * quantity / event.quantity.max(1)
+            };
+            fee_left -= fee;
+            self.realized.push(RealizedSale {
+                item_id: event.item_id,
+                quantity,
+                cost_each: lot.cost_each,
+                sell_each: event.price_each,
+                fee,
+                bought_at: lot.bought_at,
+                sold_at: event.timestamp,
+                seller: lot.seller,
+                channel: event.channel,
+            });
+            lot.quantity -= quantity;
+            remaining -= quantity;
+            if lot.quantity == 0 {
+                lots.pop_front();
+            }
+        }
+        if remaining > 0 {
+            self.unmatched.push(LedgerEvent {
+                quantity: remaining,
+                fee: fee_left,
+                ..event
+            });
+        }
+    }
+
+    pub fn realized(&self) -> &[RealizedSale] {
+        &self.realized
+    }
+
+    pub fn unmatched(&self) -> &[LedgerEvent] {
+        &self.unmatched
+    }
+
+    /// 未卖出的批次
+    pub fn open_lots(&self) -> impl Iterator<Item = &Lot> {
+        self.lots.values().flatten()
+    }
+
+    pub fn total_profit(&self) -> i64 {
+        self.realized.iter().map(|x| x.profit()).sum()
+    }
+
+    /// 按物品统计已实现利润
+    pub fn profit_by_item(&self) -> BTreeMap<i32, i64> {
+        self.group_profit(|x| x.item_id)
+    }
+
+    /// 按卖出日期(UTC)统计已实现利润，key 为当天 0 点的时间戳
+    pub fn profit_by_day(&self) -> BTreeMap<i64, i64> {
+        self.group_profit(|x| x.sold_at.div_euclid(DAY_SEC) * DAY_SEC)
+    }
+
+    /// 按买入时的卖家统计已实现利润，没有卖家信息的归到 0
+    pub fn profit_by_seller(&self) -> BTreeMap<i64, i64> {
+        self.group_profit(|x| x.seller.unwrap_or_default())
+    }
+
+    fn group_profit<K: Ord>(&self, key: impl Fn(&RealizedSale) -> K) -> BTreeMap<K, i64> {
+        let mut res = BTreeMap::new();
+        for sale in self.realized.iter() {
+            *res.entry(key(sale)).or_default() += sale.profit();
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod ledger_test {
+    use super::*;
+    use crate::test_util::ledger_logs;
+
+    #[test]
+    fn test_ledger_from_logs() {
+        let ledger = Ledger::from_logs(&ledger_logs());
+
+        // 卖出 3 个：2 个来自 800000 的批次，1 个来自 810000 的批次，费用每个 45000
+        // 商店卖出 1 个：来自 810000 的批次
+        // 交易卖出 1 个：来自卖家 1003 的 800000 的批次，交易金额 950000
+        let res: Vec<(u64, u64, i64)> = ledger
+            .realized()
+            .iter()
+            .map(|x| (x.quantity, x.cost_each, x.profit()))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                (2, 800000, 110000),
+                (1, 810000, 45000),
+                (1, 810000, -110000),
+                (1, 800000, 150000)
+            ]
+        );
+        let trade = ledger.realized().last().unwrap();
+        assert_eq!(trade.channel, LedgerChannel::Trade);
+        assert_eq!(trade.sold_at, 1760836200);
+        assert_eq!(ledger.total_profit(), 195000);
+        assert_eq!(ledger.profit_by_item(), BTreeMap::from([(206, 195000)]));
+        assert_eq!(
+            ledger.profit_by_day(),
+            BTreeMap::from([(1760832000, 195000)])
+        );
+        assert_eq!(
+            ledger.profit_by_seller(),
+            BTreeMap::from([(1001, 110000), (1002, -65000), (1003, 150000)])
+        );
+        assert!(ledger.unmatched().is_empty());
+        assert_eq!(ledger.open_lots().count(), 0);
+    }
+
+    #[test]
+    fn test_trade_remainder() {
+        use crate::trade::TradeOffer;
+
+        // 3 个物品 1000，余数 1 分给第一个物品
+        let trade = Trade {
+            user: 3001,
+            status: TradeStatus::Accepted,
+            ours: TradeOffer {
+                money: 1000,
+                ..Default::default()
+            },
+            theirs: TradeOffer {
+                money: 0,
+                items: BTreeMap::from([(206, 2), (260, 1)]),
+            },
+            started_at: 10,
+            updated_at: 20,
+            has_initiate: true,
+            log_ids: vec!["t1".to_string(), "t2".to_string()],
+        };
+        let events = LedgerEvent::from_trade(&trade);
+        let res: Vec<(LedgerSide, i32, u64, u64)> = events
+            .iter()
+            .map(|x| (x.side, x.item_id, x.quantity, x.price_each))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                (LedgerSide::Buy, 206, 1, 334),
+                (LedgerSide::Buy, 206, 1, 333),
+                (LedgerSide::Buy, 260, 1, 333),
+            ]
+        );
+
+        // 拆开的两个单价都计入批次，总成本等于交易金额
+        let mut ledger = Ledger::default();
+        ledger.add_events(&mut events.clone());
+        let cost = ledger
+            .open_lots()
+            .map(|x| x.quantity * x.cost_each)
+            .sum::<u64>();
+        assert_eq!(cost, 1000);
+    }
+
+    #[test]
+    fn test_unmatched_and_dedupe() {
+        let event = |log_id: &str, timestamp: i64, side: LedgerSide, quantity: u64| LedgerEvent {
+            log_id: log_id.to_string(),
+            timestamp,
+            side,
+            channel: LedgerChannel::Bazaar,
+            item_id: 1,
+            quantity,
+            price_each: 100,
+            fee: 0,
+            counterparty: None,
+        };
+        let mut ledger = Ledger::default();
+        ledger.add_events(&mut [
+            event("b1", 10, LedgerSide::Buy, 2),
+            event("s1", 20, LedgerSide::Sell, 3),
+            // 重复的日志
+            event("b1", 10, LedgerSide::Buy, 2),
+        ]);
+        assert_eq!(ledger.realized().len(), 1);
+        assert_eq!(ledger.unmatched().len(), 1);
+        assert_eq!(ledger.unmatched()[0].quantity, 1);
+    }
+}
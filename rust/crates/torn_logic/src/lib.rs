@@ -1,2 +1,9 @@
 pub mod trade;
-pub mod item;
\ No newline at end of file
+pub mod item;
+pub mod museum;
+pub mod ledger;
+pub mod rate_limit;
+pub mod log_store;
+/// 测试共用的数据构造
+#[cfg(test)]
+mod test_util;
\ No newline at end of file
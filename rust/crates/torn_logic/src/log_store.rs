@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, HashSet};
+
+use model::{
+    error::MyError,
+    user_log::{UserLogEntry, UserLogReq},
+};
+use serde::{Deserialize, Serialize};
+
+/// 同步方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncKind {
+    /// 拉取上次同步之后的新日志
+    Forward,
+    /// 向前补齐历史日志
+    Backfill,
+}
+
+/// 一页日志请求的时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncPage {
+    pub kind: SyncKind,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl SyncPage {
+    pub fn to_req(&self, limit: u32) -> UserLogReq {
+        UserLogReq {
+            limit,
+            from: self.from,
+            to: self.to,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserLogStoreFile {
+    version: u32,
+    sync_from: Option<i64>,
+    forward_to: Option<i64>,
+    backfill_done: bool,
+    entries: Vec<UserLogEntry>,
+}
+
+/// 本地保存的用户日志，按 id 去重
+/// Torn 每页从最新的日志开始返回，新日志超过一页时先补齐中间缺少的部分再推进同步位置
+#[derive(Debug, Clone, Default)]
+pub struct UserLogStore {
+    entries: BTreeMap<(i64, String), UserLogEntry>,
+    ids: HashSet<String>,
+    /// 这个时间之前的新日志已经同步完成
+    sync_from: Option<i64>,
+    /// 正在补齐 sync_from 到 forward_to 之间的日志
+    forward_to: Option<i64>,
+    /// 已经没有更早的日志
+    backfill_done: bool,
+}
+
+impl UserLogStore {
+    pub const FILE_VERSION: u32 = 1;
+    pub const STORE_PATH: &str = "user://user_log.json";
+
+    /// 添加日志，返回新增的数量
+    pub fn merge(&mut self, entries: Vec<UserLogEntry>) -> usize {
+        let mut count = 0;
+        for entry in entries {
+            if self.ids.insert(entry.id.clone()) {
+                self.entries
+                    .insert((entry.timestamp, entry.id.clone()), entry);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 按时间从早到晚排列
+    pub fn entries(&self) -> Vec<UserLogEntry> {
+        self.entries.values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn oldest_timestamp(&self) -> Option<i64> {
+        self.entries.keys().next().map(|x| x.0)
+    }
+
+    pub fn newest_timestamp(&self) -> Option<i64> {
+        self.entries.keys().next_back().map(|x| x.0)
+    }
+
+    pub fn is_backfill_done(&self) -> bool {
+        self.backfill_done
+    }
+
+    /// 新日志的请求范围
+    pub fn forward_page(&self) -> SyncPage {
+        SyncPage {
+            kind: SyncKind::Forward,
+            from: self.sync_from,
+            to: self.forward_to,
+        }
+    }
+
+    /// 历史日志的请求范围，已经补齐或还没有日志时返回 None
+    pub fn backfill_page(&self) -> Option<SyncPage> {
+        if self.backfill_done {
+            return None;
+        }
+        Some(SyncPage {
+            kind: SyncKind::Backfill,
+            from: None,
+            to: Some(self.oldest_timestamp()?),
+        })
+    }
+
+    /// 处理一页响应，limit 为请求的数量，返回新增的数量
+    pub fn apply_page(
+        &mut self,
+        page: SyncPage,
+        entries: Vec<UserLogEntry>,
+        limit: usize,
+    ) -> usize {
+        let is_full = entries.len() >= limit;
+        let oldest = entries.iter().map(|x| x.timestamp).min();
+        let added = self.merge(entries);
+        match page.kind {
+            SyncKind::Forward => match (self.sync_from, oldest) {
+                // 第一次同步，更早的日志交给历史补齐
+                (None, _) => {
+                    self.sync_from = self.newest_timestamp();
+                    self.forward_to = None;
+                }
+                // 这一页没有覆盖到上次同步的位置，继续补齐中间的日志
+                // 同一时间的日志超过一页时无法继续分页，直接跳过
+                (Some(_), Some(oldest)) if is_full && page.to != Some(oldest) => {
+                    self.forward_to = Some(oldest);
+                }
+                (Some(_), _) => {
+                    self.sync_from = self.newest_timestamp().or(self.sync_from);
+                    self.forward_to = None;
+                }
+            },
+            SyncKind::Backfill => {
+                if added == 0 {
+                    self.backfill_done = true;
+                }
+            }
+        }
+        added
+    }
+
+    pub fn to_json(&self) -> Result<String, MyError> {
+        let file = UserLogStoreFile {
+            version: Self::FILE_VERSION,
+            sync_from: self.sync_from,
+            forward_to: self.forward_to,
+            backfill_done: self.backfill_done,
+            entries: self.entries(),
+        };
+        serde_json::to_string(&file).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MyError> {
+        let file: UserLogStoreFile =
+            serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))?;
+        let mut res = Self {
+            sync_from: file.sync_from,
+            forward_to: file.forward_to,
+            backfill_done: file.backfill_done,
+            ..Default::default()
+        };
+        res.merge(file.entries);
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod log_store_test {
+    use super::*;
+    use crate::test_util::{ledger_logs, log_entry};
+    use model::torn::api::TornApiRequest;
+
+    /// 模拟 Torn 的分页：时间范围内最新的 limit 条
+    fn page(all: &[UserLogEntry], page: SyncPage, limit: usize) -> Vec<UserLogEntry> {
+        let mut res: Vec<UserLogEntry> = all
+            .iter()
+            .filter(|x| page.from.is_none_or(|from| x.timestamp >= from))
+            .filter(|x| page.to.is_none_or(|to| x.timestamp <= to))
+            .cloned()
+            .collect();
+        res.sort_by_key(|x| std::cmp::Reverse(x.timestamp));
+        res.truncate(limit);
+        res
+    }
+
+    #[test]
+    fn test_sync() {
+        let limit = 3;
+        let mut all: Vec<UserLogEntry> = (1..=5)
+            .map(|i| log_entry(&format!("e{}", i), i * 10))
+            .collect();
+        let mut store = UserLogStore::default();
+        assert_eq!(store.backfill_page(), None);
+
+        // 第一次同步拿到最新的一页，剩余的交给历史补齐
+        let p = store.forward_page();
+        assert_eq!(store.apply_page(p, page(&all, p, limit), limit), 3);
+        assert_eq!(store.newest_timestamp(), Some(50));
+        while let Some(p) = store.backfill_page() {
+            store.apply_page(p, page(&all, p, limit), limit);
+        }
+        assert_eq!(store.len(), 5);
+        assert!(store.is_backfill_done());
+
+        // 新增的日志超过一页，先补齐中间的部分
+        all.extend((6..=10).map(|i| log_entry(&format!("e{}", i), i * 10)));
+        let p = store.forward_page();
+        assert_eq!(p.from, Some(50));
+        assert_eq!(store.apply_page(p, page(&all, p, limit), limit), 3);
+        let p = store.forward_page();
+        assert_eq!(p.to, Some(80));
+        store.apply_page(p, page(&all, p, limit), limit);
+        let p = store.forward_page();
+        store.apply_page(p, page(&all, p, limit), limit);
+        assert_eq!(store.forward_page().from, Some(100));
+        assert_eq!(store.forward_page().to, None);
+        assert_eq!(store.len(), 10);
+
+        // 重复的日志不会重复保存
+        assert_eq!(store.merge(all.clone()), 0);
+
+        let restored = UserLogStore::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(restored.len(), 10);
+        assert_eq!(restored.forward_page(), store.forward_page());
+        assert!(restored.is_backfill_done());
+    }
+
+    #[test]
+    fn test_to_req() {
+        let p = SyncPage {
+            kind: SyncKind::Backfill,
+            from: None,
+            to: Some(100),
+        };
+        assert_eq!(
+            p.to_req(50).url(),
+            "https://api.torn.com/v2/user/log?limit=50&to=100"
+        );
+        let mut store = UserLogStore::default();
+        store.merge(ledger_logs());
+        let ids: Vec<String> = store.entries().into_iter().map(|x| x.id).collect();
+        assert_eq!(
+            ids,
+            vec!["a1", "a2", "a3", "a4", "a5", "a6", "a7", "a8", "a9"]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+/// 博物馆套装，集齐一套可以兑换点数
+#[derive(Debug, Clone, PartialEq)]
+pub struct MuseumSet {
+    pub name: &'static str,
+    /// 套装需要的物品，每种一个
+    pub item_ids: &'static [i32],
+    /// 每套兑换的点数
+    pub points: u64,
+}
+
+/// 花卉套装
+pub const FLOWER_SET: MuseumSet = MuseumSet {
+    name: "Exotic Flowers",
+    item_ids: &[260, 264, 282, 277, 276, 271, 272, 263, 267, 385, 617],
+    points: 10,
+};
+
+/// 玩偶套装
+pub const PLUSHIE_SET: MuseumSet = MuseumSet {
+    name: "Plushies",
+    item_ids: &[
+        186, 187, 215, 258, 261, 266, 268, 269, 273, 274, 281, 384, 618,
+    ],
+    points: 10,
+};
+
+pub const MUSEUM_SETS: [MuseumSet; 2] = [FLOWER_SET, PLUSHIE_SET];
+
+/// 查找物品所属的套装
+pub fn find_set(item_id: i32) -> Option<&'static MuseumSet> {
+    MUSEUM_SETS.iter().find(|x| x.item_ids.contains(&item_id))
+}
+
+/// 套装中单个物品的估值
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceValue {
+    pub item_id: i32,
+    /// 当前最便宜的挂单价格
+    pub cheapest: Option<u64>,
+    /// 边际价值：套装价值减去其他缺少物品的购买成本
+    /// 按这个价格买入刚好保本，其他缺少的物品没有挂单时为 None
+    pub marginal_value: Option<u64>,
+}
+
+/// 套装估值
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetValuation {
+    pub name: &'static str,
+    /// 一套兑换的点数价值
+    pub set_value: u64,
+    /// 补齐一套需要的购买成本，有物品没有挂单时为 None
+    pub completion_cost: Option<u64>,
+    pub pieces: Vec<PieceValue>,
+}
+
+impl SetValuation {
+    pub fn piece(&self, item_id: i32) -> Option<&PieceValue> {
+        self.pieces.iter().find(|x| x.item_id == item_id)
+    }
+
+    /// 补齐一套的利润
+    pub fn completion_profit(&self) -> Option<i64> {
+        self.completion_cost
+            .map(|cost| self.set_value as i64 - cost as i64)
+    }
+}
+
+impl MuseumSet {
+    /// 一套兑换的点数价值
+    pub fn value(&self, points_price: u64) -> u64 {
+        self.points * points_price
+    }
+
+    /// 按点数价格和最便宜的挂单估值，每种物品都需要购买
+    pub fn valuation(&self, points_price: u64, cheapest: &HashMap<i32, u64>) -> SetValuation {
+        let set_value = self.value(points_price);
+        // 其他物品的购买成本，没有挂单时为 None
+        let missing_cost = |except: Option<i32>| {
+            self.item_ids
+                .iter()
+                .filter(|id| Some(**id) != except)
+                .map(|id| cheapest.get(id).copied())
+                .sum::<Option<u64>>()
+        };
+        let pieces = self
+            .item_ids
+            .iter()
+            .map(|id| PieceValue {
+                item_id: *id,
+                cheapest: cheapest.get(id).copied(),
+                marginal_value: missing_cost(Some(*id)).map(|cost| set_value.saturating_sub(cost)),
+            })
+            .collect();
+        SetValuation {
+            name: self.name,
+            set_value,
+            completion_cost: missing_cost(None),
+            pieces,
+        }
+    }
+}
+
+#[cfg(test)]
+mod museum_test {
+    use super::*;
+
+    const TEST_SET: MuseumSet = MuseumSet {
+        name: "test",
+        item_ids: &[1, 2, 3],
+        points: 10,
+    };
+
+    #[test]
+    fn test_find_set() {
+        assert_eq!(find_set(385).map(|x| x.name), Some("Exotic Flowers"));
+        assert_eq!(find_set(186).map(|x| x.name), Some("Plushies"));
+        assert_eq!(find_set(206), None);
+        // 同一物品不能属于多个套装
+        for set in MUSEUM_SETS.iter() {
+            for id in set.item_ids {
+                assert_eq!(find_set(*id).map(|x| x.name), Some(set.name));
+            }
+        }
+    }
+
+    #[test]
+    fn test_valuation() {
+        let cheapest = HashMap::from([(1, 100), (2, 200), (3, 300)]);
+        let res = TEST_SET.valuation(80, &cheapest);
+        assert_eq!(res.set_value, 800);
+        assert_eq!(res.completion_cost, Some(600));
+        assert_eq!(res.completion_profit(), Some(200));
+        // 物品 1 的边际价值 = 800 - 200 - 300
+        assert_eq!(res.piece(1).unwrap().marginal_value, Some(300));
+        assert_eq!(res.piece(3).unwrap().marginal_value, Some(500));
+
+        // 物品 3 没有挂单，其他物品无法估值
+        let cheapest = HashMap::from([(1, 100), (2, 200)]);
+        let res = TEST_SET.valuation(80, &cheapest);
+        assert_eq!(res.completion_cost, None);
+        assert_eq!(res.piece(1).unwrap().marginal_value, None);
+        assert_eq!(res.piece(3).unwrap().marginal_value, Some(500));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Torn API 每个 key 每分钟最多 100 次请求，默认预留一部分给其他工具
+pub const DEFAULT_BUDGET_PER_MINUTE: u32 = 60;
+/// 第一次收到 "too many requests" 后的等待时间，连续出现时翻倍
+const BACKOFF_SEC: u64 = 60;
+const MAX_BACKOFF_SEC: u64 = 600;
+const MINUTE_SEC: u64 = 60;
+
+/// 请求优先级，数值越小越先处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RequestPriority {
+    /// 用户主动触发的查询
+    Interactive,
+    /// 定时刷新的价格
+    Normal,
+    /// 后台同步
+    Background,
+}
+
+/// 申请结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Acquire {
+    /// 可以立即发送
+    Granted,
+    /// 排队中，position 为前面等待的请求数量
+    Queued { position: usize },
+    /// 收到过 "too many requests"，until 之前不发送
+    BackingOff { until: u64 },
+}
+
+#[derive(Debug, Clone)]
+struct Waiting {
+    ticket: String,
+    priority: RequestPriority,
+    enqueued_at: u64,
+    last_seen: u64,
+}
+
+#[derive(Debug, Clone)]
+struct KeyState {
+    tokens: f64,
+    last_refill: u64,
+    used: VecDeque<u64>,
+    backoff_sec: u64,
+    backoff_until: u64,
+    waiting: Vec<Waiting>,
+}
+
+/// Torn API 令牌桶限流，每个 key 单独计算
+/// 调用方用同一个 ticket 重复申请，未获批准时保持排队，高优先级的请求先获批准
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// 每个 key 每分钟的请求预算
+    pub budget_per_minute: u32,
+    /// 超过这个时间没有再次申请的排队请求会被移除
+    pub queue_timeout_sec: u64,
+    /// 超过这个时间没有再次申请的排队请求不再阻塞后面的请求，再次申请时恢复原来的位置
+    pub poll_timeout_sec: u64,
+    keys: HashMap<String, KeyState>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUDGET_PER_MINUTE)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(budget_per_minute: u32) -> Self {
+        Self {
+            budget_per_minute,
+            queue_timeout_sec: 30,
+            poll_timeout_sec: 5,
+            keys: HashMap::new(),
+        }
+    }
+
+    fn state(&mut self, key: &str, now: u64) -> &mut KeyState {
+        let budget = self.budget_per_minute.max(1) as f64;
+        let timeout = self.queue_timeout_sec;
+        let state = self
+            .keys
+            .entry(key.to_string())
+            .or_insert_with(|| KeyState {
+                tokens: budget,
+                last_refill: now,
+                used: VecDeque::new(),
+                backoff_sec: 0,
+                backoff_until: 0,
+                waiting: Vec::new(),
+            });
+        let elapsed = now.saturating_sub(state.last_refill) as f64;
+        state.tokens = (state.tokens + elapsed * budget / MINUTE_SEC as f64).min(budget);
+        state.last_refill = state.last_refill.max(now);
+        while state
+            .used
+            .front()
+            .is_some_and(|x| now.saturating_sub(*x) >= MINUTE_SEC)
+        {
+            state.used.pop_front();
+        }
+        state
+            .waiting
+            .retain(|x| now.saturating_sub(x.last_seen) <= timeout);
+        state
+    }
+
+    /// 申请发送一次请求，Granted 时会扣除预算
+    pub fn acquire(
+        &mut self,
+        key: &str,
+        ticket: &str,
+        priority: RequestPriority,
+        now: u64,
+    ) -> Acquire {
+        let poll_timeout = self.poll_timeout_sec;
+        let state = self.state(key, now);
+        match state.waiting.iter_mut().find(|x| x.ticket == ticket) {
+            Some(waiting) => {
+                waiting.last_seen = now;
+                waiting.priority = priority;
+            }
+            None => state.waiting.push(Waiting {
+                ticket: ticket.to_string(),
+                priority,
+                enqueued_at: now,
+                last_seen: now,
+            }),
+        }
+        if now < state.backoff_until {
+            return Acquire::BackingOff {
+                until: state.backoff_until,
+            };
+        }
+        state.waiting.sort_by_key(|x| (x.priority, x.enqueued_at));
+        let position = state
+            .waiting
+            .iter()
+            .filter(|x| now.saturating_sub(x.last_seen) <= poll_timeout)
+            .position(|x| x.ticket == ticket)
+            .unwrap_or_default();
+        if position > 0 || state.tokens < 1.0 {
+            return Acquire::Queued { position };
+        }
+        state.tokens -= 1.0;
+        state.used.push_back(now);
+        state.waiting.retain(|x| x.ticket != ticket);
+        Acquire::Granted
+    }
+
+    /// 取消排队，调用方不再重试时调用，避免阻塞后面的请求
+    pub fn cancel(&mut self, key: &str, ticket: &str) {
+        if let Some(state) = self.keys.get_mut(key) {
+            state.waiting.retain(|x| x.ticket != ticket);
+        }
+    }
+
+    /// 收到 "too many requests" 时调用，清空预算并等待
+    pub fn backoff(&mut self, key: &str, now: u64) {
+        let state = self.state(key, now);
+        state.backoff_sec = if state.backoff_sec == 0 {
+            BACKOFF_SEC
+        } else {
+            (state.backoff_sec * 2).min(MAX_BACKOFF_SEC)
+        };
+        state.backoff_until = now + state.backoff_sec;
+        state.tokens = 0.0;
+    }
+
+    /// 请求成功时调用，重置等待时间
+    pub fn on_success(&mut self, key: &str) {
+        if let Some(state) = self.keys.get_mut(key) {
+            state.backoff_sec = 0;
+        }
+    }
+
+    /// 最近一分钟已经使用的请求数量
+    pub fn used_this_minute(&mut self, key: &str, now: u64) -> usize {
+        self.state(key, now).used.len()
+    }
+
+    /// 正在排队的请求数量
+    pub fn queued(&mut self, key: &str, now: u64) -> usize {
+        self.state(key, now).waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod rate_limit_test {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let mut limiter = RateLimiter::new(2);
+        let p = RequestPriority::Normal;
+        assert_eq!(limiter.acquire("k", "a", p, 0), Acquire::Granted);
+        assert_eq!(limiter.acquire("k", "b", p, 0), Acquire::Granted);
+        assert_eq!(
+            limiter.acquire("k", "c", p, 0),
+            Acquire::Queued { position: 0 }
+        );
+        // 其他 key 不受影响
+        assert_eq!(limiter.acquire("k2", "c", p, 0), Acquire::Granted);
+        assert_eq!(limiter.used_this_minute("k", 0), 2);
+        // 30 秒恢复一个
+        assert_eq!(limiter.acquire("k", "c", p, 30), Acquire::Granted);
+        assert_eq!(limiter.used_this_minute("k", 61), 1);
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut limiter = RateLimiter::new(1);
+        assert_eq!(
+            limiter.acquire("k", "sync", RequestPriority::Background, 0),
+            Acquire::Granted
+        );
+        assert_eq!(
+            limiter.acquire("k", "sync", RequestPriority::Background, 10),
+            Acquire::Queued { position: 0 }
+        );
+        assert_eq!(
+            limiter.acquire("k", "lookup", RequestPriority::Interactive, 57),
+            Acquire::Queued { position: 0 }
+        );
+        // 预算恢复后交互请求先获批准
+        assert_eq!(
+            limiter.acquire("k", "sync", RequestPriority::Background, 60),
+            Acquire::Queued { position: 1 }
+        );
+        assert_eq!(
+            limiter.acquire("k", "lookup", RequestPriority::Interactive, 60),
+            Acquire::Granted
+        );
+        // 长时间不再申请的请求被移除
+        assert_eq!(limiter.queued("k", 100), 0);
+    }
+
+    #[test]
+    fn test_abandoned_ticket() {
+        let mut limiter = RateLimiter::new(1);
+        let p = RequestPriority::Background;
+        assert_eq!(limiter.acquire("k", "sync", p, 0), Acquire::Granted);
+        assert_eq!(
+            limiter.acquire("k", "lookup", RequestPriority::Interactive, 50),
+            Acquire::Queued { position: 0 }
+        );
+        // 交互请求没有再次申请，不再阻塞后台请求
+        assert_eq!(
+            limiter.acquire("k", "sync", p, 55),
+            Acquire::Queued { position: 1 }
+        );
+        assert_eq!(limiter.acquire("k", "sync", p, 60), Acquire::Granted);
+        assert_eq!(limiter.queued("k", 60), 1);
+
+        // 取消后立即移除
+        assert_eq!(
+            limiter.acquire("k", "lookup", RequestPriority::Interactive, 61),
+            Acquire::Queued { position: 0 }
+        );
+        assert_eq!(
+            limiter.acquire("k", "sync", p, 61),
+            Acquire::Queued { position: 1 }
+        );
+        limiter.cancel("k", "lookup");
+        assert_eq!(limiter.queued("k", 61), 1);
+        assert_eq!(limiter.acquire("k", "sync", p, 120), Acquire::Granted);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut limiter = RateLimiter::new(60);
+        let p = RequestPriority::Normal;
+        limiter.backoff("k", 0);
+        assert_eq!(
+            limiter.acquire("k", "a", p, 10),
+            Acquire::BackingOff { until: 60 }
+        );
+        assert_eq!(limiter.acquire("k", "a", p, 60), Acquire::Granted);
+        // 连续出现时等待时间翻倍，成功后重置
+        limiter.backoff("k", 60);
+        assert_eq!(
+            limiter.acquire("k", "a", p, 61),
+            Acquire::BackingOff { until: 180 }
+        );
+        limiter.on_success("k");
+        limiter.backoff("k", 200);
+        assert_eq!(
+            limiter.acquire("k", "a", p, 201),
+            Acquire::BackingOff { until: 260 }
+        );
+    }
+}
@@ -0,0 +1,22 @@
+use model::user_log::{UserLogEntry, UserLogResponse};
+
+/// 交易日志样例
+pub fn trade_logs() -> Vec<UserLogEntry> {
+    let json = include_str!("../fixtures/user_log_trade.json");
+    UserLogResponse::from_json(json).unwrap().log
+}
+
+/// 买入卖出和交易的日志样例
+pub fn ledger_logs() -> Vec<UserLogEntry> {
+    let json = include_str!("../fixtures/user_log_ledger.json");
+    UserLogResponse::from_json(json).unwrap().log
+}
+
+/// 只有 id 和时间的日志
+pub fn log_entry(id: &str, timestamp: i64) -> UserLogEntry {
+    let json = format!(
+        r#"{{"id":"{}","timestamp":{},"details":{{"id":1,"title":"t","category":"c"}},"data":{{}},"params":{{}}}}"#,
+        id, timestamp
+    );
+    serde_json::from_str(&json).unwrap()
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::OnceLock,
+};
 
 use model::user_log::UserLogEntry;
 
@@ -19,6 +22,7 @@ pub fn torn_log_trade_map() -> &'static HashMap<u32, &'static str> {
         HashMap::from([
             (4400, TORN_LOG_TRADE_INIT),
             (4410, TORN_LOG_TRADE_CANCEL_INCOME),
+            (4411, TORN_LOG_TRADE_CANCEL_OUTGOING),
             (4442, TORN_LOG_TRADE_ADD),
             (4443, TORN_LOG_TRADE_REMOVE),
             (4420, TORN_LOG_TRADE_EXPIRE),
@@ -26,21 +30,306 @@ pub fn torn_log_trade_map() -> &'static HashMap<u32, &'static str> {
     })
 }
 
-pub fn get_torn_coin_value(logs: &[UserLogEntry]) -> Option<u64> {
-    let id_map = torn_log_trade_map();
-    for log in logs.iter() {
-        if let Some(name) = id_map.get(&log.details.id) {
-            match *name {
-                TORN_LOG_TRADE_ADD | TORN_LOG_TRADE_REMOVE => match log.data.total {
-                    Some(r) => return Some(r),
-                    None => return None,
-                },
-                TORN_LOG_TRADE_CANCEL_INCOME
-                | TORN_LOG_TRADE_CANCEL_OUTGOING
-                | TORN_LOG_TRADE_EXPIRE => return None,
-                _ => continue,
+/// 交易日志对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeAction {
+    Initiate,
+    MoneyAdd,
+    MoneyRemove,
+    ItemsAdd,
+    ItemsRemove,
+    Accept,
+    Cancel,
+    Expire,
+}
+
+/// 先按日志 id 查找标题，id 未知时使用日志中的标题
+fn log_title(log: &UserLogEntry) -> &str {
+    torn_log_trade_map()
+        .get(&log.details.id)
+        .copied()
+        .unwrap_or(log.details.title.as_str())
+}
+
+impl TradeAction {
+    pub fn from_log(log: &UserLogEntry) -> Option<TradeAction> {
+        Self::from_title(log_title(log))
+    }
+
+    pub fn from_title(title: &str) -> Option<TradeAction> {
+        let title = title.to_lowercase();
+        let title = title.strip_prefix("trade ")?;
+        if title.starts_with("initiate") {
+            Some(TradeAction::Initiate)
+        } else if title.starts_with("money add") {
+            Some(TradeAction::MoneyAdd)
+        } else if title.starts_with("money remove") {
+            Some(TradeAction::MoneyRemove)
+        } else if title.starts_with("item") && title.contains("add") {
+            Some(TradeAction::ItemsAdd)
+        } else if title.starts_with("item") && title.contains("remove") {
+            Some(TradeAction::ItemsRemove)
+        } else if title.starts_with("accept") || title.starts_with("complete") {
+            Some(TradeAction::Accept)
+        } else if title.starts_with("cancel") {
+            Some(TradeAction::Cancel)
+        } else if title.starts_with("expire") {
+            Some(TradeAction::Expire)
+        } else {
+            None
+        }
+    }
+
+    /// 同一时间的日志的处理顺序：发起最先，结束最后
+    fn order(&self) -> u8 {
+        match self {
+            TradeAction::Initiate => 0,
+            TradeAction::Accept | TradeAction::Cancel | TradeAction::Expire => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// 改动金钱或物品的一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeParty {
+    /// 自己
+    Ours,
+    /// 交易对象
+    Theirs,
+}
+
+impl TradeParty {
+    pub fn from_log(log: &UserLogEntry) -> TradeParty {
+        Self::from_title(log_title(log))
+    }
+
+    /// 交易对象的改动标题以 other 结尾，例如 "Trade money add other"
+    pub fn from_title(title: &str) -> TradeParty {
+        if title.to_lowercase().trim_end().ends_with(" other") {
+            TradeParty::Theirs
+        } else {
+            TradeParty::Ours
+        }
+    }
+}
+
+/// 交易一方放入的金钱和物品
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeOffer {
+    pub money: u64,
+    /// 物品净数量，移除多于添加时为负数
+    pub items: BTreeMap<i32, i64>,
+}
+
+impl TradeOffer {
+    pub fn is_empty(&self) -> bool {
+        self.money == 0 && self.items.is_empty()
+    }
+}
+
+/// 交易状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeStatus {
+    /// 已发起，还没有改动
+    Initiated,
+    /// 已添加或移除金钱、物品
+    Modified,
+    Accepted,
+    Cancelled,
+    Expired,
+}
+
+impl TradeStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TradeStatus::Accepted | TradeStatus::Cancelled | TradeStatus::Expired
+        )
+    }
+}
+
+/// 一次交易，由同一交易对象的连续日志重建
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    /// 交易对象
+    pub user: i64,
+    pub status: TradeStatus,
+    /// 自己放入的金钱和物品
+    pub ours: TradeOffer,
+    /// 交易对象放入的金钱和物品
+    pub theirs: TradeOffer,
+    pub started_at: i64,
+    pub updated_at: i64,
+    /// 是否有发起日志，日志不完整时为 false
+    pub has_initiate: bool,
+    pub log_ids: Vec<String>,
+}
+
+impl Trade {
+    fn new(user: i64, timestamp: i64) -> Self {
+        Self {
+            user,
+            status: TradeStatus::Initiated,
+            ours: TradeOffer::default(),
+            theirs: TradeOffer::default(),
+            started_at: timestamp,
+            updated_at: timestamp,
+            has_initiate: false,
+            log_ids: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, action: TradeAction, log: &UserLogEntry) {
+        self.updated_at = log.timestamp;
+        self.log_ids.push(log.id.clone());
+        let data = &log.data;
+        let offer = match TradeParty::from_log(log) {
+            TradeParty::Ours => &mut self.ours,
+            TradeParty::Theirs => &mut self.theirs,
+        };
+        self.status = match action {
+            TradeAction::Initiate => {
+                self.has_initiate = true;
+                self.status
+            }
+            TradeAction::MoneyAdd | TradeAction::MoneyRemove => {
+                // total 是改动后的金额，没有 total 时按改动累加
+                let money = data.money.unwrap_or_default();
+                offer.money = match (data.total, action) {
+                    (Some(total), _) => total,
+                    (None, TradeAction::MoneyAdd) => offer.money + money,
+                    (None, _) => offer.money.saturating_sub(money),
+                };
+                TradeStatus::Modified
+            }
+            TradeAction::ItemsAdd | TradeAction::ItemsRemove => {
+                let sign = if action == TradeAction::ItemsAdd {
+                    1
+                } else {
+                    -1
+                };
+                for item in data.items.iter() {
+                    *offer.items.entry(item.id).or_default() += sign * item.qty as i64;
+                }
+                offer.items.retain(|_, qty| *qty != 0);
+                TradeStatus::Modified
             }
+            TradeAction::Accept => TradeStatus::Accepted,
+            TradeAction::Cancel => TradeStatus::Cancelled,
+            TradeAction::Expire => TradeStatus::Expired,
+        };
+    }
+}
+
+/// 从用户日志重建交易，日志可以是任意顺序，非交易日志会被忽略
+/// 同一交易对象的交易结束后再出现日志时视为新的交易，返回结果按开始时间排序
+pub fn reconstruct_trades(logs: &[UserLogEntry]) -> Vec<Trade> {
+    let mut entries: Vec<(TradeAction, &UserLogEntry)> = logs
+        .iter()
+        .filter_map(|log| TradeAction::from_log(log).map(|action| (action, log)))
+        .collect();
+    entries.sort_by(|(a, x), (b, y)| {
+        (x.timestamp, a.order(), &x.id).cmp(&(y.timestamp, b.order(), &y.id))
+    });
+
+    let mut open: HashMap<i64, Trade> = HashMap::new();
+    let mut res = Vec::new();
+    for (action, log) in entries {
+        let user = log.data.user;
+        // 未结束的交易收到新的发起日志，说明之前的结束日志缺失
+        if action == TradeAction::Initiate
+            && let Some(trade) = open.remove(&user)
+        {
+            log::warn!("reconstruct_trades: trade with {} has no end log", user);
+            res.push(trade);
+        }
+        let trade = open
+            .entry(user)
+            .or_insert_with(|| Trade::new(user, log.timestamp));
+        trade.apply(action, log);
+        if trade.status.is_finished()
+            && let Some(trade) = open.remove(&user)
+        {
+            res.push(trade);
         }
     }
-    None
+    res.extend(open.into_values());
+    res.sort_by_key(|x| (x.started_at, x.user));
+    res
+}
+
+/// 最近一次交易中双方的金钱合计，只有交易已完成时返回
+pub fn get_torn_coin_value(logs: &[UserLogEntry]) -> Option<u64> {
+    let trade = reconstruct_trades(logs)
+        .into_iter()
+        .max_by_key(|x| x.updated_at)?;
+    let money = trade.ours.money + trade.theirs.money;
+    match trade.status {
+        TradeStatus::Accepted if money > 0 => Some(money),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod trade_test {
+    use super::*;
+    use crate::test_util::trade_logs;
+
+    #[test]
+    fn test_trade_action() {
+        assert_eq!(
+            TradeAction::from_title(TORN_LOG_TRADE_CANCEL_OUTGOING),
+            Some(TradeAction::Cancel)
+        );
+        assert_eq!(
+            TradeAction::from_title("Trade items add"),
+            Some(TradeAction::ItemsAdd)
+        );
+        assert_eq!(
+            TradeAction::from_title("Trade item remove"),
+            Some(TradeAction::ItemsRemove)
+        );
+        assert_eq!(TradeAction::from_title("Bazaar buy"), None);
+        assert_eq!(
+            TradeParty::from_title("Trade items add other"),
+            TradeParty::Theirs
+        );
+        assert_eq!(TradeParty::from_title("Trade items add"), TradeParty::Ours);
+    }
+
+    #[test]
+    fn test_reconstruct_trades() {
+        let trades = reconstruct_trades(&trade_logs());
+        let res: Vec<(i64, TradeStatus, u64, bool)> = trades
+            .iter()
+            .map(|x| (x.user, x.status, x.ours.money, x.has_initiate))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                (3001, TradeStatus::Accepted, 400000, true),
+                (3002, TradeStatus::Cancelled, 1000, true),
+                // 缺少发起日志，金额按改动累加
+                (3001, TradeStatus::Expired, 2000, false),
+            ]
+        );
+        // 交易对象放入物品，自己放入金钱
+        assert!(trades[0].ours.items.is_empty());
+        assert_eq!(trades[0].theirs.items, BTreeMap::from([(206, 2)]));
+        assert!(trades[1].theirs.is_empty());
+        assert_eq!(trades[0].log_ids, vec!["t1", "t2", "t3", "t4", "t5"]);
+    }
+
+    #[test]
+    fn test_get_torn_coin_value() {
+        let mut logs = trade_logs();
+        // 最近的交易已过期
+        assert_eq!(get_torn_coin_value(&logs), None);
+        // 最近的交易还没有完成
+        logs.retain(|x| x.timestamp < 1760746215);
+        assert_eq!(get_torn_coin_value(&logs), None);
+        logs.retain(|x| x.timestamp < 1760746000);
+        assert_eq!(get_torn_coin_value(&logs), Some(400000));
+    }
 }
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use crate::profit::{ProfitInfo, ProfitUserInfo};
+
+/// 提醒条件
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertCondition {
+    /// 指定物品单价低于某个价格，`item:206<800000`
+    ItemPriceBelow { id: i32, price: u64 },
+    /// 单个物品净利润高于某个值，`profit>100000`
+    NetProfitAbove(i64),
+    /// 收藏的卖家，`favorite`
+    FavoriteSeller,
+    /// 指定类型的物品利润百分比高于某个值，`type:Plushie>5`
+    ItemTypePercentageAbove { item_type: String, percentage: f32 },
+}
+
+impl AlertCondition {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("favorite") {
+            return Some(AlertCondition::FavoriteSeller);
+        }
+        if let Some(rest) = text.strip_prefix("item:") {
+            let (id, price) = rest.split_once('<')?;
+            return Some(AlertCondition::ItemPriceBelow {
+                id: id.trim().parse().ok()?,
+                price: price.trim().parse().ok()?,
+            });
+        }
+        if let Some(rest) = text.strip_prefix("profit>") {
+            return Some(AlertCondition::NetProfitAbove(rest.trim().parse().ok()?));
+        }
+        if let Some(rest) = text.strip_prefix("type:") {
+            let (item_type, percentage) = rest.rsplit_once('>')?;
+            return Some(AlertCondition::ItemTypePercentageAbove {
+                item_type: item_type.trim().to_string(),
+                percentage: percentage.trim().parse().ok()?,
+            });
+        }
+        None
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            AlertCondition::ItemPriceBelow { id, price } => format!("item:{}<{}", id, price),
+            AlertCondition::NetProfitAbove(profit) => format!("profit>{}", profit),
+            AlertCondition::FavoriteSeller => "favorite".to_string(),
+            AlertCondition::ItemTypePercentageAbove {
+                item_type,
+                percentage,
+            } => format!("type:{}>{}", item_type, percentage),
+        }
+    }
+
+    /// 找到卖家第一个满足条件的物品
+    fn find_item<'a>(&self, user: &'a ProfitUserInfo) -> Option<&'a ProfitInfo> {
+        match self {
+            AlertCondition::ItemPriceBelow { id, price } => user
+                .items
+                .iter()
+                .find(|x| x.id == *id && x.single_recyle_price < *price),
+            AlertCondition::NetProfitAbove(profit) => user
+                .items
+                .iter()
+                .find(|x| x.final_profit.total_profit_value > *profit),
+            AlertCondition::FavoriteSeller => {
+                if user.is_favorite {
+                    user.items.first()
+                } else {
+                    None
+                }
+            }
+            AlertCondition::ItemTypePercentageAbove {
+                item_type,
+                percentage,
+            } => user.items.iter().find(|x| {
+                x.item_type.eq_ignore_ascii_case(item_type)
+                    && x.final_profit.percentage > *percentage
+            }),
+        }
+    }
+}
+
+/// 提醒动作
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertAction {
+    /// 播放声音，路径为空时播放默认提示音，`sound:res://assets/audio/xxx.mp3`
+    Sound(String),
+    /// 弹出提示，`toast`
+    Toast,
+    /// 置顶卖家，`pin`
+    Pin,
+    /// 写入日志，`log`
+    Log,
+}
+
+impl AlertAction {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(path) = text.strip_prefix("sound") {
+            return match path.strip_prefix(':') {
+                Some(path) => Some(AlertAction::Sound(path.trim().to_string())),
+                None if path.is_empty() => Some(AlertAction::Sound(String::new())),
+                None => None,
+            };
+        }
+        match text.to_lowercase().as_str() {
+            "toast" => Some(AlertAction::Toast),
+            "pin" => Some(AlertAction::Pin),
+            "log" => Some(AlertAction::Log),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            AlertAction::Sound(path) if path.is_empty() => "sound".to_string(),
+            AlertAction::Sound(path) => format!("sound:{}", path),
+            AlertAction::Toast => "toast".to_string(),
+            AlertAction::Pin => "pin".to_string(),
+            AlertAction::Log => "log".to_string(),
+        }
+    }
+}
+
+/// 提醒规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub condition: AlertCondition,
+    pub actions: Vec<AlertAction>,
+    /// 同一卖家再次触发的间隔
+    pub cooldown_sec: u64,
+}
+
+impl AlertRule {
+    /// 没有填写冷却时间时使用的默认值
+    pub const DEFAULT_COOLDOWN_SEC: u64 = 300;
+
+    /// 从文本解析，每行一个：`名称|条件|动作,动作|冷却秒数`
+    /// 冷却秒数省略或无法解析时使用 DEFAULT_COOLDOWN_SEC，填 0 表示每次都触发
+    pub fn parse_lines(text: &str) -> Vec<AlertRule> {
+        text.lines()
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split('|').map(|x| x.trim()).collect();
+                let rule = match parts.as_slice() {
+                    [name, condition, actions, rest @ ..] => {
+                        AlertCondition::parse(condition).map(|condition| AlertRule {
+                            name: name.to_string(),
+                            condition,
+                            actions: actions.split(',').filter_map(AlertAction::parse).collect(),
+                            cooldown_sec: Self::parse_cooldown(rest.first().copied(), line),
+                        })
+                    }
+                    _ => None,
+                };
+                if rule.is_none() {
+                    log::warn!("AlertRule: invalid rule, line: {}", line);
+                }
+                rule
+            })
+            .collect()
+    }
+
+    fn parse_cooldown(text: Option<&str>, line: &str) -> u64 {
+        match text.filter(|x| !x.is_empty()).map(|x| x.parse::<u64>()) {
+            Some(Ok(sec)) => sec,
+            Some(Err(_)) => {
+                log::warn!("AlertRule: invalid cooldown, use default, line: {}", line);
+                Self::DEFAULT_COOLDOWN_SEC
+            }
+            None => Self::DEFAULT_COOLDOWN_SEC,
+        }
+    }
+
+    /// 转换为文本，与 parse_lines 对应
+    pub fn format_lines(rules: &[AlertRule]) -> String {
+        rules
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}|{}|{}|{}",
+                    x.name,
+                    x.condition.format(),
+                    x.actions
+                        .iter()
+                        .map(|x| x.format())
+                        .collect::<Vec<String>>()
+                        .join(","),
+                    x.cooldown_sec
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// 触发的提醒
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    pub rule_name: String,
+    pub player_id: i32,
+    pub actions: Vec<AlertAction>,
+    pub message: String,
+}
+
+/// 提醒规则引擎，按规则和卖家记录冷却时间
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+    pub rules: Vec<AlertRule>,
+    /// (规则名称, player_id) -> 上次触发时间
+    last_fired: HashMap<(String, i32), u64>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    /// 检查所有卖家，返回本次触发的提醒，每条规则每个卖家最多触发一次
+    pub fn evaluate(&mut self, users: &[ProfitUserInfo], now: u64) -> Vec<AlertEvent> {
+        let mut events = Vec::new();
+        for rule in self.rules.iter() {
+            for user in users.iter() {
+                let Some(item) = rule.condition.find_item(user) else {
+                    continue;
+                };
+                let key = (rule.name.clone(), user.player_id);
+                if let Some(last) = self.last_fired.get(&key)
+                    && now < last + rule.cooldown_sec
+                {
+                    continue;
+                }
+                self.last_fired.insert(key, now);
+                events.push(AlertEvent {
+                    rule_name: rule.name.clone(),
+                    player_id: user.player_id,
+                    actions: rule.actions.clone(),
+                    message: format!(
+                        "{}: {} {} x{} @{} Profit:{}",
+                        rule.name,
+                        user.player_name,
+                        item.name,
+                        item.quantity,
+                        item.single_recyle_price,
+                        item.final_profit.total_profit_value
+                    ),
+                });
+            }
+        }
+        // 清理已经过了冷却时间的记录
+        let max_cooldown = self.rules.iter().map(|x| x.cooldown_sec).max();
+        self.last_fired
+            .retain(|_, last| max_cooldown.is_some_and(|x| now < *last + x));
+        events
+    }
+}
+
+#[cfg(test)]
+mod alert_test {
+    use super::*;
+    use crate::test_util::{profit_info, user};
+
+    fn plushie(player_id: i32, price: u64) -> ProfitUserInfo {
+        user(ProfitInfo {
+            item_type: "Plushie".to_string(),
+            ..profit_info(player_id, 206, 1, price, 10, 6.0)
+        })
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let text = "cheap xanax|item:206<800000|toast,sound:res://a.mp3|300\n\
+            big|profit>100000|pin,log,unknown|0\n\
+            fav|favorite|sound|60\n\
+            plushie|type:Plushie>5|toast|0\n\
+            bad|price<1|toast|0\n\
+            short|favorite\n\
+            default|favorite|toast\n\
+            invalid|favorite|toast|soon";
+        let rules = AlertRule::parse_lines(text);
+        assert_eq!(rules.len(), 6);
+        assert_eq!(
+            rules[0].condition,
+            AlertCondition::ItemPriceBelow {
+                id: 206,
+                price: 800000
+            }
+        );
+        assert_eq!(
+            rules[0].actions,
+            vec![
+                AlertAction::Toast,
+                AlertAction::Sound("res://a.mp3".to_string())
+            ]
+        );
+        assert_eq!(rules[1].actions, vec![AlertAction::Pin, AlertAction::Log]);
+        assert_eq!(rules[2].actions, vec![AlertAction::Sound(String::new())]);
+        // 冷却时间省略或无法解析时使用默认值，明确填 0 时每次都触发
+        let cooldowns: Vec<u64> = rules.iter().map(|x| x.cooldown_sec).collect();
+        assert_eq!(
+            cooldowns,
+            vec![
+                300,
+                0,
+                60,
+                0,
+                AlertRule::DEFAULT_COOLDOWN_SEC,
+                AlertRule::DEFAULT_COOLDOWN_SEC
+            ]
+        );
+        assert_eq!(
+            AlertRule::parse_lines(&AlertRule::format_lines(&rules)),
+            rules
+        );
+    }
+
+    #[test]
+    fn test_evaluate_cooldown() {
+        let mut engine = AlertEngine {
+            rules: AlertRule::parse_lines(
+                "cheap|item:206<1000|toast|60\nplushie|type:plushie>5|log|0",
+            ),
+            ..Default::default()
+        };
+        let users = vec![plushie(1, 900), plushie(2, 1100)];
+
+        let events = engine.evaluate(&users, 100);
+        let res: Vec<(&str, i32)> = events
+            .iter()
+            .map(|x| (x.rule_name.as_str(), x.player_id))
+            .collect();
+        assert_eq!(res, vec![("cheap", 1), ("plushie", 1), ("plushie", 2)]);
+        assert_eq!(
+            events[0].message,
+            "cheap: player-1 item-206 x1 @900 Profit:10"
+        );
+
+        // 冷却时间内不再触发，没有冷却时间的规则每次都触发
+        let events = engine.evaluate(&users, 130);
+        assert_eq!(events.len(), 2);
+        let events = engine.evaluate(&users, 160);
+        assert_eq!(events.len(), 3);
+    }
+}
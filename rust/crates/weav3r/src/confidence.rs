@@ -0,0 +1,168 @@
+use model::items::ItemInfo;
+
+/// 满分
+pub const MAX_CONFIDENCE: f32 = 100.0;
+
+/// 扣分原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfidenceReason {
+    /// 市场价和集市均价差距过大，值为差距百分比
+    PriceSpread(f32),
+    /// 流通量过低
+    LowCirculation(u64),
+    /// 市场价偏离物品列表中的市场价值，值为偏离百分比
+    MarketValueDeviation(f32),
+    /// 最便宜的挂单数量过少
+    FewListings(usize),
+    /// 物品列表中没有该物品
+    UnknownItem,
+}
+
+impl ConfidenceReason {
+    pub fn describe(&self) -> String {
+        match self {
+            ConfidenceReason::PriceSpread(spread) => format!("价差 {:.1}%", spread),
+            ConfidenceReason::LowCirculation(circulation) => format!("流通量 {}", circulation),
+            ConfidenceReason::MarketValueDeviation(deviation) => {
+                format!("偏离市场价值 {:.1}%", deviation)
+            }
+            ConfidenceReason::FewListings(count) => format!("挂单数 {}", count),
+            ConfidenceReason::UnknownItem => "未知物品".to_string(),
+        }
+    }
+}
+
+/// 交易可信度，分数 0 ~ 100，越高越可信
+#[derive(Debug, Clone, PartialEq)]
+pub struct DealConfidence {
+    pub score: f32,
+    /// 扣分原因和扣除的分数
+    pub reasons: Vec<(ConfidenceReason, f32)>,
+}
+
+impl Default for DealConfidence {
+    fn default() -> Self {
+        Self {
+            score: MAX_CONFIDENCE,
+            reasons: Vec::new(),
+        }
+    }
+}
+
+impl DealConfidence {
+    /// 根据价格和物品信息评估可信度
+    /// - 市场价和集市均价差距越大，价格越不可靠
+    /// - 流通量越低，越难按参考价格卖出
+    /// - 市场价偏离物品列表中的市场价值，可能是被短时间炒高
+    /// - 最便宜的挂单越少，价格越容易被操纵
+    pub fn evaluate(
+        market_price: i64,
+        avg_bazaar_price: i64,
+        item: Option<&ItemInfo>,
+        listing_count: usize,
+    ) -> Self {
+        let mut res = DealConfidence::default();
+
+        let low = market_price.min(avg_bazaar_price);
+        if low > 0 {
+            let spread = (market_price - avg_bazaar_price).abs() as f32 / low as f32 * 100.0;
+            // 10% 以内不扣分，之后每 1% 扣 1 分
+            res.deduct(
+                ConfidenceReason::PriceSpread(spread),
+                (spread - 10.0).min(40.0),
+            );
+        }
+
+        match item {
+            Some(item) => {
+                let penalty = match item.circulation {
+                    0..1_000 => 25.0,
+                    1_000..10_000 => 15.0,
+                    10_000..100_000 => 5.0,
+                    _ => 0.0,
+                };
+                res.deduct(ConfidenceReason::LowCirculation(item.circulation), penalty);
+
+                if item.market_value > 0 {
+                    let deviation = (market_price - item.market_value as i64).abs() as f32
+                        / item.market_value as f32
+                        * 100.0;
+                    // 20% 以内不扣分，之后每 2% 扣 1 分
+                    res.deduct(
+                        ConfidenceReason::MarketValueDeviation(deviation),
+                        ((deviation - 20.0) / 2.0).min(25.0),
+                    );
+                }
+            }
+            None => res.deduct(ConfidenceReason::UnknownItem, 10.0),
+        }
+
+        let penalty = match listing_count {
+            0 | 1 => 15.0,
+            2 => 5.0,
+            _ => 0.0,
+        };
+        res.deduct(ConfidenceReason::FewListings(listing_count), penalty);
+
+        res
+    }
+
+    fn deduct(&mut self, reason: ConfidenceReason, penalty: f32) {
+        if penalty <= 0.0 {
+            return;
+        }
+        self.score = (self.score - penalty).max(0.0);
+        self.reasons.push((reason, penalty));
+    }
+
+    /// 扣分原因说明，多个原因用逗号分隔
+    pub fn describe(&self) -> String {
+        self.reasons
+            .iter()
+            .map(|(reason, penalty)| format!("{} -{:.0}", reason.describe(), penalty))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod confidence_test {
+    use super::*;
+
+    fn item(circulation: u64, market_value: u64) -> ItemInfo {
+        ItemInfo {
+            id: 206,
+            circulation,
+            market_value,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_reliable() {
+        let res = DealConfidence::evaluate(1000, 980, Some(&item(1_000_000, 1050)), 5);
+        assert_eq!(res.score, MAX_CONFIDENCE);
+        assert!(res.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_trap() {
+        // 价差 100%，流通量低，偏离市场价值 100%，只有一个挂单
+        let res = DealConfidence::evaluate(2000, 1000, Some(&item(500, 1000)), 1);
+        let reasons: Vec<&ConfidenceReason> = res.reasons.iter().map(|x| &x.0).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                &ConfidenceReason::PriceSpread(100.0),
+                &ConfidenceReason::LowCirculation(500),
+                &ConfidenceReason::MarketValueDeviation(100.0),
+                &ConfidenceReason::FewListings(1),
+            ]
+        );
+        assert_eq!(res.score, 0.0);
+
+        let res = DealConfidence::evaluate(1200, 1000, None, 2);
+        assert_eq!(res.score, 100.0 - 10.0 - 10.0 - 5.0);
+        assert_eq!(res.describe(), "价差 20.0% -10, 未知物品 -10, 挂单数 2 -5");
+    }
+}
@@ -1,5 +1,17 @@
+use std::collections::HashMap;
+
 use tools::cfg::CfgTool;
 
+use crate::{
+    alert::AlertRule,
+    fee::FeeSchedule,
+    history::PriceReference,
+    planner::PlanParams,
+    player::PlayerList,
+    profit::{EquipmentMode, FilterItem},
+    sort::{SORT_PRESETS, SortKey},
+};
+
 pub struct Weav3rSettingData {
     cfg: CfgTool,
 }
@@ -30,18 +42,66 @@ impl Weav3rSettingData {
     const KEY_FILTER_IDS: &str = "filter_ids";
     const DEFAULT_FILTER_IDS: &str = "385,260,903,263,617,272,264,271,267,277,282,276,186,187,215,261,618,273,258,266,268,269,281,274,384,533,555,532,554,530,553,987,986,985,206,586,587,151,556,529,528,36,527,310,35,210,39,37,209,38,541,552,542,638,551,531,550,818,283,370,364,1080,1079,1082,1083,1078,1081,367,366,369";
 
+    /// 单个物品过滤条件，每行一个：id,最高价格,最小利润百分比,名称
+    const KEY_FILTER_ITEMS: &str = "filter_items";
+    const DEFAULT_FILTER_ITEMS: &str = "";
+
+    /// 玩家屏蔽、收藏和备注，每行一个：player_id,block/favorite,备注
+    const KEY_PLAYERS: &str = "players";
+    const DEFAULT_PLAYERS: &str = "";
+    const KEY_ALERT_RULES: &str = "alert_rules";
+    const DEFAULT_ALERT_RULES: &str = "";
+
+    /// 出售费用，每行一个：渠道,百分比,固定费用,物品类型
+    const KEY_SELL_FEES: &str = "sell_fees";
+    const DEFAULT_SELL_FEES: &str = "";
+
+    /// 购买计划可用资金
+    const KEY_PLAN_BUDGET: &str = "plan_budget";
+    const DEFAULT_PLAN_BUDGET: i64 = 0;
+    /// 购买计划单个物品数量限制，格式：id:数量,id:数量
+    const KEY_PLAN_QUANTITY_CAPS: &str = "plan_quantity_caps";
+    const DEFAULT_PLAN_QUANTITY_CAPS: &str = "";
+
+    /// 利润计算使用的参考价格，格式：current、median:窗口秒数、average:窗口秒数
+    const KEY_PRICE_REFERENCE: &str = "price_reference";
+    const DEFAULT_PRICE_REFERENCE: &str = "current";
+    /// 武器和防具只按基础价值计算，关闭时不计算利润
+    const KEY_EQUIPMENT_BASE_VALUE: &str = "equipment_base_value";
+    const DEFAULT_EQUIPMENT_BASE_VALUE: bool = false;
+    /// 价格历史保留天数
+    const KEY_HISTORY_DAYS: &str = "history_days";
+    const DEFAULT_HISTORY_DAYS: i64 = 7;
+
+    /// 最低可信度
+    const KEY_MIN_CONFIDENCE: &str = "min_confidence";
+    const DEFAULT_MIN_CONFIDENCE: f32 = 0.0;
+    /// 排序条件，格式：字段:asc/desc,字段:asc/desc
+    const KEY_SORT_STRATEGY: &str = "sort_strategy";
+    const DEFAULT_SORT_STRATEGY: &str = SORT_PRESETS[0].1;
+
     /// 官方回收最低价
     const KEY_OFFICE_SELL_PRICE: &str = "office_sell_price";
     const DEFAULT_OFFICE_SELL_PRICE: i64 = 5000;
     /// 回收利润阀值
     const KEY_OFFICE_SELL_PROFIT: &str = "office_sell_profit";
     const DEFAULT_OFFICE_SELL_PROFIT: i64 = 5000;
+    /// 手动设置的点数价格，没有拉取到点数价格时使用，为 0 时不计算博物馆利润
+    const KEY_POINTS_PRICE: &str = "points_price";
+    const DEFAULT_POINTS_PRICE: i64 = 0;
 
-        pub const KEY_NEXT_ACTION: &str = "next_action";
+    pub const KEY_NEXT_ACTION: &str = "next_action";
     const DEFAULT_NEXT_ACTION: &str = "";
 
     pub const KEY_COOKIE: &str = "Cookie";
     const DEFAULT_COOKIE: &str = "";
+
+    /// Torn API key
+    const KEY_API_KEY: &str = "api_key";
+    const DEFAULT_API_KEY: &str = "";
+    /// 每个 Torn API key 每分钟的请求预算
+    const KEY_TORN_API_BUDGET: &str = "torn_api_budget";
+    const DEFAULT_TORN_API_BUDGET: i64 = torn_logic::rate_limit::DEFAULT_BUDGET_PER_MINUTE as i64;
 }
 
 impl Weav3rSettingData {
@@ -105,6 +165,38 @@ impl Weav3rSettingData {
         );
     }
 
+    pub fn get_filter_items(&self) -> Vec<FilterItem> {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_FILTER_ITEMS,
+            Weav3rSettingData::DEFAULT_FILTER_ITEMS,
+        );
+        FilterItem::parse_lines(&text)
+    }
+    pub fn set_filter_items(&mut self, filter_items: &[FilterItem]) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_FILTER_ITEMS,
+            FilterItem::format_lines(filter_items).as_str(),
+        );
+    }
+
+    pub fn get_sell_fees(&self) -> FeeSchedule {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_SELL_FEES,
+            Weav3rSettingData::DEFAULT_SELL_FEES,
+        );
+        FeeSchedule::parse_lines(&text)
+    }
+    pub fn set_sell_fees(&mut self, fees: &FeeSchedule) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_SELL_FEES,
+            fees.format_lines().as_str(),
+        );
+    }
+
     pub fn get_audio_switch(&self) -> bool {
         self.cfg.read_config_bool(
             Weav3rSettingData::SECTION,
@@ -127,7 +219,7 @@ impl Weav3rSettingData {
             Weav3rSettingData::DEFAULT_LIGHT_SEC,
         )
     }
-    
+
     pub fn set_light_sec(&mut self, light_sec: u16) {
         self.cfg.write_config_u16(
             Weav3rSettingData::SECTION,
@@ -166,6 +258,21 @@ impl Weav3rSettingData {
         );
     }
 
+    pub fn get_api_key(&self) -> String {
+        self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_API_KEY,
+            Weav3rSettingData::DEFAULT_API_KEY,
+        )
+    }
+    pub fn set_api_key(&mut self, api_key: &str) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_API_KEY,
+            api_key,
+        );
+    }
+
     pub fn get_office_sell_price(&self) -> i64 {
         self.cfg.read_config_i64(
             Weav3rSettingData::SECTION,
@@ -181,6 +288,36 @@ impl Weav3rSettingData {
         );
     }
 
+    pub fn get_points_price(&self) -> i64 {
+        self.cfg.read_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_POINTS_PRICE,
+            Weav3rSettingData::DEFAULT_POINTS_PRICE,
+        )
+    }
+    pub fn set_points_price(&mut self, points_price: i64) {
+        self.cfg.write_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_POINTS_PRICE,
+            points_price,
+        );
+    }
+
+    pub fn get_torn_api_budget(&self) -> i64 {
+        self.cfg.read_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_TORN_API_BUDGET,
+            Weav3rSettingData::DEFAULT_TORN_API_BUDGET,
+        )
+    }
+    pub fn set_torn_api_budget(&mut self, budget: i64) {
+        self.cfg.write_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_TORN_API_BUDGET,
+            budget,
+        );
+    }
+
     pub fn get_office_sell_profit(&self) -> i64 {
         self.cfg.read_config_i64(
             Weav3rSettingData::SECTION,
@@ -213,6 +350,165 @@ impl Weav3rSettingData {
         );
     }
 
+    pub fn get_plan_budget(&self) -> i64 {
+        self.cfg.read_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAN_BUDGET,
+            Weav3rSettingData::DEFAULT_PLAN_BUDGET,
+        )
+    }
+
+    pub fn set_plan_budget(&mut self, value: i64) {
+        self.cfg.write_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAN_BUDGET,
+            value,
+        );
+    }
+
+    pub fn get_plan_quantity_caps(&self) -> HashMap<i32, i32> {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAN_QUANTITY_CAPS,
+            Weav3rSettingData::DEFAULT_PLAN_QUANTITY_CAPS,
+        );
+        PlanParams::parse_quantity_caps(&text)
+    }
+
+    pub fn set_plan_quantity_caps(&mut self, caps: &HashMap<i32, i32>) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAN_QUANTITY_CAPS,
+            PlanParams::format_quantity_caps(caps).as_str(),
+        );
+    }
+
+    pub fn get_price_reference(&self) -> PriceReference {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PRICE_REFERENCE,
+            Weav3rSettingData::DEFAULT_PRICE_REFERENCE,
+        );
+        PriceReference::parse(&text)
+    }
+
+    pub fn set_price_reference(&mut self, reference: PriceReference) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PRICE_REFERENCE,
+            reference.format().as_str(),
+        );
+    }
+
+    pub fn get_history_days(&self) -> i64 {
+        self.cfg.read_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_HISTORY_DAYS,
+            Weav3rSettingData::DEFAULT_HISTORY_DAYS,
+        )
+    }
+
+    pub fn set_history_days(&mut self, days: i64) {
+        self.cfg.write_config_i64(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_HISTORY_DAYS,
+            days,
+        );
+    }
+
+    pub fn get_equipment_mode(&self) -> EquipmentMode {
+        let base_value = self.cfg.read_config_bool(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_EQUIPMENT_BASE_VALUE,
+            Weav3rSettingData::DEFAULT_EQUIPMENT_BASE_VALUE,
+        );
+        if base_value {
+            EquipmentMode::BaseValue
+        } else {
+            EquipmentMode::Exclude
+        }
+    }
+
+    pub fn set_equipment_mode(&mut self, mode: EquipmentMode) {
+        self.cfg.write_config_bool(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_EQUIPMENT_BASE_VALUE,
+            mode == EquipmentMode::BaseValue,
+        );
+    }
+
+    pub fn get_min_confidence(&self) -> f32 {
+        self.cfg.read_config_f32(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_MIN_CONFIDENCE,
+            Weav3rSettingData::DEFAULT_MIN_CONFIDENCE,
+        )
+    }
+
+    pub fn set_min_confidence(&mut self, min_confidence: f32) {
+        self.cfg.write_config_f32(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_MIN_CONFIDENCE,
+            min_confidence,
+        );
+    }
+
+    /// 排序条件，配置为空或无效时使用默认排序
+    pub fn get_sort_strategy(&self) -> Vec<SortKey> {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_SORT_STRATEGY,
+            Weav3rSettingData::DEFAULT_SORT_STRATEGY,
+        );
+        let keys = SortKey::parse_list(&text);
+        if keys.is_empty() {
+            return SortKey::parse_list(Weav3rSettingData::DEFAULT_SORT_STRATEGY);
+        }
+        keys
+    }
+
+    pub fn set_sort_strategy(&mut self, keys: &[SortKey]) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_SORT_STRATEGY,
+            SortKey::format_list(keys).as_str(),
+        );
+    }
+
+    pub fn get_players(&self) -> PlayerList {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAYERS,
+            Weav3rSettingData::DEFAULT_PLAYERS,
+        );
+        PlayerList::parse_lines(&text)
+    }
+
+    pub fn set_players(&mut self, players: &PlayerList) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_PLAYERS,
+            players.format_lines().as_str(),
+        );
+    }
+
+    pub fn get_alert_rules(&self) -> Vec<AlertRule> {
+        let text = self.cfg.read_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_ALERT_RULES,
+            Weav3rSettingData::DEFAULT_ALERT_RULES,
+        );
+        AlertRule::parse_lines(&text)
+    }
+
+    pub fn set_alert_rules(&mut self, rules: &[AlertRule]) {
+        self.cfg.write_config_string(
+            Weav3rSettingData::SECTION,
+            Weav3rSettingData::KEY_ALERT_RULES,
+            AlertRule::format_lines(rules).as_str(),
+        );
+    }
+
     pub fn save(&mut self) -> Result<(), godot::global::Error> {
         self.cfg.save()?;
         Ok(())
@@ -0,0 +1,217 @@
+use model::{error::MyError, weav3r::favorites::FavoritesResponse};
+
+/// 第一次失败后的等待时间，连续失败时翻倍
+const BACKOFF_SEC: u64 = 30;
+const MAX_BACKOFF_SEC: u64 = 600;
+
+/// 请求失败后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// 需要用户更新设置，停止轮询
+    OpenSettings,
+    /// 暂时性错误，等待后继续轮询
+    Backoff,
+}
+
+/// 解析 weav3r 收藏接口的响应，失败时区分错误类型
+pub fn parse_response(
+    code: i64,
+    headers: &[String],
+    body: &str,
+) -> Result<FavoritesResponse, MyError> {
+    if is_cloudflare(code, headers, body) {
+        return Err(MyError::Weav3rCloudflare(format!("code: {}", code)));
+    }
+    if is_action_not_found(headers, body) {
+        return Err(MyError::Weav3rActionOutdated(format!("code: {}", code)));
+    }
+    // Cloudflare 限流返回 1015 错误页
+    if code == 429 || body.contains("error code: 1015") {
+        let retry_after = header(headers, "retry-after")
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or_default();
+        return Err(MyError::Weav3rRateLimited(retry_after));
+    }
+    if code >= 500 {
+        return Err(MyError::Weav3rServer(code, snippet(body)));
+    }
+    if code != 200 {
+        return Err(MyError::NetworkCode(code, "weav3r favorites".to_string()));
+    }
+    // action id 过期时旧版本 Next.js 会直接返回页面
+    if is_html(headers, body) {
+        return Err(MyError::Weav3rActionOutdated("html page".to_string()));
+    }
+    FavoritesResponse::from_text(body)
+}
+
+/// 错误对应的处理方式
+pub fn recovery(err: &MyError) -> Recovery {
+    match err {
+        MyError::Weav3rActionOutdated(_) | MyError::Weav3rCloudflare(_) => Recovery::OpenSettings,
+        _ => Recovery::Backoff,
+    }
+}
+
+/// 提示给用户的信息
+pub fn user_message(err: &MyError) -> String {
+    match err {
+        MyError::Weav3rActionOutdated(_) => {
+            "next-action 已过期，请在设置中重新解析 curl".to_string()
+        }
+        MyError::Weav3rCloudflare(_) => {
+            "Cloudflare 验证失败，cf_clearance 可能已过期，请在设置中更新 cookie".to_string()
+        }
+        MyError::Weav3rRateLimited(_) => "请求过于频繁，稍后自动重试".to_string(),
+        MyError::Weav3rServer(..) | MyError::Weav3rFlight(..) => {
+            "weav3r 服务器错误，稍后自动重试".to_string()
+        }
+        _ => "请求失败，稍后自动重试".to_string(),
+    }
+}
+
+/// 暂时性错误的等待时间
+#[derive(Debug, Clone, Default)]
+pub struct FailureBackoff {
+    failures: u32,
+    until: u64,
+}
+
+impl FailureBackoff {
+    /// 记录一次失败，返回可以再次请求的时间
+    pub fn on_failure(&mut self, err: &MyError, now: u64) -> u64 {
+        let delay = (BACKOFF_SEC << self.failures.min(16)).min(MAX_BACKOFF_SEC);
+        let delay = match err {
+            MyError::Weav3rRateLimited(retry_after) => delay.max(*retry_after),
+            _ => delay,
+        };
+        self.failures += 1;
+        self.until = now + delay;
+        self.until
+    }
+
+    pub fn on_success(&mut self) {
+        self.failures = 0;
+        self.until = 0;
+    }
+
+    pub fn is_waiting(&self, now: u64) -> bool {
+        now < self.until
+    }
+}
+
+/// 响应头格式为 `Name: value`，名称不区分大小写
+fn header<'a>(headers: &'a [String], name: &str) -> Option<&'a str> {
+    headers.iter().find_map(|x| {
+        let (key, value) = x.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then_some(value.trim())
+    })
+}
+
+fn is_html(headers: &[String], body: &str) -> bool {
+    header(headers, "content-type").is_some_and(|x| x.contains("text/html"))
+        || body.trim_start().starts_with('<')
+}
+
+/// 验证页面或 cf_clearance 过期，Cloudflare 会返回 cf-mitigated 头或验证页面
+fn is_cloudflare(code: i64, headers: &[String], body: &str) -> bool {
+    if header(headers, "cf-mitigated").is_some_and(|x| x.eq_ignore_ascii_case("challenge")) {
+        return true;
+    }
+    if !is_html(headers, body) {
+        return false;
+    }
+    body.contains("challenge-platform")
+        || body.contains("cf_chl_")
+        || ((code == 403 || code == 503) && body.contains("Just a moment..."))
+}
+
+/// Next.js 找不到 action id 时返回 404 和提示文本
+fn is_action_not_found(headers: &[String], body: &str) -> bool {
+    header(headers, "x-nextjs-action-not-found").is_some()
+        || body.contains("Server action not found")
+        || body.contains("Failed to find Server Action")
+}
+
+/// 日志中只保留开头部分
+fn snippet(body: &str) -> String {
+    body.chars().take(200).collect()
+}
+
+#[cfg(test)]
+mod failure_test {
+    use super::*;
+
+    fn headers(list: &[&str]) -> Vec<String> {
+        list.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let html = headers(&["Content-Type: text/html; charset=UTF-8"]);
+        let challenge = "<!DOCTYPE html><html><head><title>Just a moment...</title></head>\
+            <body><script src=\"/cdn-cgi/challenge-platform/h/g/orchestrate/chl_page/v1\"></script></body></html>";
+        let res = parse_response(403, &html, challenge);
+        assert!(matches!(res, Err(MyError::Weav3rCloudflare(_))));
+        let res = parse_response(403, &headers(&["cf-mitigated: challenge"]), "");
+        assert!(matches!(res, Err(MyError::Weav3rCloudflare(_))));
+
+        let res = parse_response(404, &headers(&[]), "Server action not found.");
+        assert!(matches!(res, Err(MyError::Weav3rActionOutdated(_))));
+        let res = parse_response(200, &html, "<!DOCTYPE html><html></html>");
+        assert!(matches!(res, Err(MyError::Weav3rActionOutdated(_))));
+
+        let res = parse_response(429, &headers(&["Retry-After: 120"]), "");
+        assert!(matches!(res, Err(MyError::Weav3rRateLimited(120))));
+        let res = parse_response(403, &html, "<html>error code: 1015</html>");
+        assert!(matches!(res, Err(MyError::Weav3rRateLimited(0))));
+
+        let res = parse_response(502, &html, "<html>Bad gateway</html>");
+        assert!(matches!(res, Err(MyError::Weav3rServer(502, _))));
+        let res = parse_response(
+            200,
+            &headers(&[]),
+            "0:{\"a\":\"$@1\"}\n1:E{\"digest\":\"1\"}\n",
+        );
+        assert!(matches!(res, Err(MyError::Weav3rFlight(..))));
+
+        let text = include_str!("../fixtures/weav3r_favorites.txt");
+        let res = parse_response(200, &headers(&["Content-Type: text/x-component"]), text);
+        assert_eq!(res.unwrap().items.len(), 2);
+    }
+
+    #[test]
+    fn test_recovery_and_backoff() {
+        assert_eq!(
+            recovery(&MyError::Weav3rCloudflare(String::new())),
+            Recovery::OpenSettings
+        );
+        assert_eq!(
+            recovery(&MyError::Weav3rActionOutdated(String::new())),
+            Recovery::OpenSettings
+        );
+        assert_eq!(
+            recovery(&MyError::Weav3rServer(500, String::new())),
+            Recovery::Backoff
+        );
+
+        let mut backoff = FailureBackoff::default();
+        let server = MyError::Weav3rServer(500, String::new());
+        assert_eq!(backoff.on_failure(&server, 0), 30);
+        assert!(backoff.is_waiting(29));
+        assert_eq!(backoff.on_failure(&server, 30), 90);
+        // 限流时至少等待 Retry-After
+        assert_eq!(
+            backoff.on_failure(&MyError::Weav3rRateLimited(300), 100),
+            400
+        );
+        backoff.on_success();
+        assert!(!backoff.is_waiting(100));
+        for _ in 0..10 {
+            backoff.on_failure(&server, 0);
+        }
+        assert_eq!(backoff.on_failure(&server, 0), MAX_BACKOFF_SEC);
+    }
+}
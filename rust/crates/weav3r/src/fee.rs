@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+/// 出售渠道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SellChannel {
+    /// 物品市场
+    Market,
+    /// 集市
+    Bazaar,
+    /// 官方回收
+    Office,
+}
+
+impl SellChannel {
+    pub fn parse(channel: &str) -> Option<Self> {
+        match channel.trim().to_lowercase().as_str() {
+            "market" => Some(SellChannel::Market),
+            "bazaar" => Some(SellChannel::Bazaar),
+            "office" => Some(SellChannel::Office),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SellChannel::Market => "market",
+            SellChannel::Bazaar => "bazaar",
+            SellChannel::Office => "office",
+        }
+    }
+}
+
+/// 单个渠道的出售费用：按比例扣除后再扣除固定金额
+/// 挂单费、匿名挂单费、快速出售需要的降价都折算到这里
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelFee {
+    /// 费用百分比
+    pub percentage: f32,
+    /// 单个物品固定费用
+    pub flat: u64,
+}
+
+impl ChannelFee {
+    /// 扣除费用后单个物品实际到手价格
+    pub fn net_price(&self, price: u64) -> u64 {
+        let percentage_fee = (price as f64 * self.percentage as f64 / 100.0).round() as u64;
+        price
+            .saturating_sub(percentage_fee)
+            .saturating_sub(self.flat)
+    }
+}
+
+/// 各个渠道的费用
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelFees {
+    pub market: ChannelFee,
+    pub bazaar: ChannelFee,
+    pub office: ChannelFee,
+}
+
+impl ChannelFees {
+    pub fn get(&self, channel: SellChannel) -> &ChannelFee {
+        match channel {
+            SellChannel::Market => &self.market,
+            SellChannel::Bazaar => &self.bazaar,
+            SellChannel::Office => &self.office,
+        }
+    }
+
+    fn get_mut(&mut self, channel: SellChannel) -> &mut ChannelFee {
+        match channel {
+            SellChannel::Market => &mut self.market,
+            SellChannel::Bazaar => &mut self.bazaar,
+            SellChannel::Office => &mut self.office,
+        }
+    }
+}
+
+/// 出售费用配置，物品类型配置优先，否则使用默认配置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeSchedule {
+    pub default: ChannelFees,
+    /// 物品类型(ItemInfo.t) -> 费用
+    pub item_types: HashMap<String, ChannelFees>,
+}
+
+impl FeeSchedule {
+    /// 获取物品类型对应的费用
+    pub fn fees(&self, item_type: Option<&str>) -> &ChannelFees {
+        item_type
+            .and_then(|t| self.item_types.get(t))
+            .unwrap_or(&self.default)
+    }
+
+    /// 从文本解析，每行一个：`渠道,百分比,固定费用,物品类型`
+    /// 渠道为 market/bazaar/office，物品类型为空表示默认配置
+    /// 物品类型只覆盖配置过的渠道，其他渠道沿用默认配置
+    pub fn parse_lines(text: &str) -> FeeSchedule {
+        let mut schedule = FeeSchedule::default();
+        let mut type_fees: Vec<(String, SellChannel, ChannelFee)> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(4, ',').map(|x| x.trim());
+            let Some(channel) = parts.next().and_then(SellChannel::parse) else {
+                log::warn!("FeeSchedule: invalid channel, line: {}", line);
+                continue;
+            };
+            let fee = ChannelFee {
+                percentage: parts
+                    .next()
+                    .and_then(|x| x.parse::<f32>().ok())
+                    .unwrap_or_default(),
+                flat: parts
+                    .next()
+                    .and_then(|x| x.parse::<u64>().ok())
+                    .unwrap_or_default(),
+            };
+            match parts.next().filter(|x| !x.is_empty()) {
+                Some(item_type) => type_fees.push((item_type.to_string(), channel, fee)),
+                None => *schedule.default.get_mut(channel) = fee,
+            }
+        }
+
+        for (item_type, channel, fee) in type_fees {
+            let fees = schedule
+                .item_types
+                .entry(item_type)
+                .or_insert_with(|| schedule.default.clone());
+            *fees.get_mut(channel) = fee;
+        }
+        schedule
+    }
+
+    /// 转换为文本，与 parse_lines 对应
+    pub fn format_lines(&self) -> String {
+        let channels = [
+            SellChannel::Market,
+            SellChannel::Bazaar,
+            SellChannel::Office,
+        ];
+        let mut lines: Vec<String> = channels
+            .iter()
+            .map(|c| format_line(*c, self.default.get(*c), ""))
+            .collect();
+
+        let mut item_types: Vec<&String> = self.item_types.keys().collect();
+        item_types.sort();
+        for item_type in item_types {
+            let fees = &self.item_types[item_type];
+            for c in channels.iter() {
+                if fees.get(*c) != self.default.get(*c) {
+                    lines.push(format_line(*c, fees.get(*c), item_type));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn format_line(channel: SellChannel, fee: &ChannelFee, item_type: &str) -> String {
+    format!(
+        "{},{},{},{}",
+        channel.as_str(),
+        fee.percentage,
+        fee.flat,
+        item_type
+    )
+}
+
+#[cfg(test)]
+mod fee_test {
+    use super::*;
+
+    #[test]
+    fn test_net_price() {
+        let fee = ChannelFee {
+            percentage: 5.0,
+            flat: 100,
+        };
+        assert_eq!(fee.net_price(10000), 9400);
+        assert_eq!(fee.net_price(50), 0);
+        assert_eq!(ChannelFee::default().net_price(10000), 10000);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let schedule =
+            FeeSchedule::parse_lines("market,8,0,Plushie\nmarket,5,10\nbazaar,1,\nunknown,1,1");
+        assert_eq!(
+            schedule.fees(None).market,
+            ChannelFee {
+                percentage: 5.0,
+                flat: 10
+            }
+        );
+        assert_eq!(schedule.fees(None).bazaar.percentage, 1.0);
+
+        let plushie = schedule.fees(Some("Plushie"));
+        assert_eq!(plushie.market.percentage, 8.0);
+        assert_eq!(plushie.market.flat, 0);
+        assert_eq!(plushie.bazaar.percentage, 1.0);
+        assert_eq!(schedule.fees(Some("Flower")), schedule.fees(None));
+
+        assert_eq!(FeeSchedule::parse_lines(&schedule.format_lines()), schedule);
+    }
+}
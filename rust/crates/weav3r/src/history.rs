@@ -0,0 +1,342 @@
+use std::collections::{HashMap, VecDeque};
+
+use model::{error::MyError, weav3r::favorites::ProductionItem};
+use serde::{Deserialize, Serialize};
+
+/// 单次拉取的价格快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceSnapshot {
+    #[serde(rename = "t")]
+    pub timestamp: u64,
+    #[serde(rename = "m")]
+    pub market_price: Option<i64>,
+    #[serde(rename = "b")]
+    pub avg_bazaar_price: Option<i64>,
+    /// 最便宜的集市价格
+    #[serde(rename = "c")]
+    pub cheapest_price: Option<i64>,
+}
+
+impl PriceSnapshot {
+    pub fn from_production(product: &ProductionItem, timestamp: u64) -> Self {
+        Self {
+            timestamp,
+            market_price: product.market_price.filter(|x| *x > 0),
+            avg_bazaar_price: product.avg_bazaar_price.filter(|x| *x > 0),
+            cheapest_price: product
+                .cheapest_bazaars
+                .iter()
+                .map(|x| x.price)
+                .filter(|x| *x > 0)
+                .min(),
+        }
+    }
+
+    pub fn get(&self, field: PriceField) -> Option<i64> {
+        match field {
+            PriceField::Market => self.market_price,
+            PriceField::AvgBazaar => self.avg_bazaar_price,
+            PriceField::Cheapest => self.cheapest_price,
+        }
+    }
+}
+
+/// 价格类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceField {
+    Market,
+    AvgBazaar,
+    Cheapest,
+}
+
+/// 历史数据保留策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRetention {
+    /// 最长保留时间
+    pub max_age_sec: u64,
+    /// 单个物品最多保留的快照数量
+    pub max_snapshots: usize,
+    /// 同一物品两次快照的最小间隔，间隔内的数据不记录
+    pub min_interval_sec: u64,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_age_sec: 7 * 24 * 3600,
+            max_snapshots: 2016,
+            min_interval_sec: 300,
+        }
+    }
+}
+
+/// 窗口内的价格统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceStats {
+    pub count: usize,
+    pub avg: i64,
+    pub min: i64,
+    pub max: i64,
+    pub median: i64,
+}
+
+/// 利润计算使用的参考价格
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PriceReference {
+    /// 当前价格
+    #[default]
+    Current,
+    /// 窗口内平均价
+    Average(u64),
+    /// 窗口内中位数
+    Median(u64),
+}
+
+impl PriceReference {
+    /// 解析配置，格式：`current`、`average:窗口秒数`、`median:窗口秒数`
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim().to_lowercase();
+        let (kind, window) = match text.split_once(':') {
+            Some((kind, window)) => (kind.trim(), window.trim().parse::<u64>().ok()),
+            None => (text.as_str(), None),
+        };
+        match (kind, window) {
+            ("average", Some(window)) if window > 0 => PriceReference::Average(window),
+            ("median", Some(window)) if window > 0 => PriceReference::Median(window),
+            _ => PriceReference::Current,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            PriceReference::Current => "current".to_string(),
+            PriceReference::Average(window) => format!("average:{}", window),
+            PriceReference::Median(window) => format!("median:{}", window),
+        }
+    }
+}
+
+/// 本地价格历史，按物品 id 保存快照
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory {
+    pub retention: HistoryRetention,
+    items: HashMap<i32, VecDeque<PriceSnapshot>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PriceHistoryFile {
+    version: u32,
+    items: HashMap<i32, Vec<PriceSnapshot>>,
+}
+
+impl PriceHistory {
+    pub const FILE_VERSION: u32 = 1;
+    pub const HISTORY_PATH: &str = "user://price_history.json";
+
+    /// 记录一次拉取结果，返回是否有新增快照
+    pub fn record(&mut self, productions: &[ProductionItem], now: u64) -> bool {
+        let mut changed = false;
+        for product in productions.iter() {
+            let snapshot = PriceSnapshot::from_production(product, now);
+            let list = self.items.entry(product.id).or_default();
+            if let Some(last) = list.back()
+                && now < last.timestamp + self.retention.min_interval_sec
+            {
+                continue;
+            }
+            list.push_back(snapshot);
+            changed = true;
+        }
+        self.prune(now);
+        changed
+    }
+
+    /// 按保留策略清理过期数据
+    pub fn prune(&mut self, now: u64) {
+        let min_time = now.saturating_sub(self.retention.max_age_sec);
+        for list in self.items.values_mut() {
+            while list.front().is_some_and(|x| x.timestamp < min_time) {
+                list.pop_front();
+            }
+            while list.len() > self.retention.max_snapshots {
+                list.pop_front();
+            }
+        }
+        self.items.retain(|_, list| !list.is_empty());
+    }
+
+    pub fn snapshots(&self, id: i32) -> impl Iterator<Item = &PriceSnapshot> {
+        self.items.get(&id).into_iter().flat_map(|x| x.iter())
+    }
+
+    /// 统计最近 window_sec 秒内的价格
+    pub fn stats(
+        &self,
+        id: i32,
+        field: PriceField,
+        window_sec: u64,
+        now: u64,
+    ) -> Option<PriceStats> {
+        let min_time = now.saturating_sub(window_sec);
+        let mut values: Vec<i64> = self
+            .snapshots(id)
+            .filter(|x| x.timestamp >= min_time)
+            .filter_map(|x| x.get(field))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort();
+        let count = values.len();
+        let median = if count.is_multiple_of(2) {
+            (values[count / 2 - 1] + values[count / 2]) / 2
+        } else {
+            values[count / 2]
+        };
+        Some(PriceStats {
+            count,
+            avg: values.iter().sum::<i64>() / count as i64,
+            min: values[0],
+            max: values[count - 1],
+            median,
+        })
+    }
+
+    /// 按参考价格配置获取价格，没有历史数据时返回 None
+    pub fn reference_price(
+        &self,
+        id: i32,
+        field: PriceField,
+        reference: PriceReference,
+        now: u64,
+    ) -> Option<i64> {
+        match reference {
+            PriceReference::Current => None,
+            PriceReference::Average(window) => self.stats(id, field, window, now).map(|x| x.avg),
+            PriceReference::Median(window) => self.stats(id, field, window, now).map(|x| x.median),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, MyError> {
+        let file = PriceHistoryFile {
+            version: Self::FILE_VERSION,
+            items: self
+                .items
+                .iter()
+                .map(|(id, list)| (*id, list.iter().cloned().collect()))
+                .collect(),
+        };
+        serde_json::to_string(&file).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+
+    pub fn from_json(json: &str, retention: HistoryRetention) -> Result<Self, MyError> {
+        let file: PriceHistoryFile =
+            serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))?;
+        Ok(Self {
+            retention,
+            items: file
+                .items
+                .into_iter()
+                .map(|(id, mut list)| {
+                    list.sort_by_key(|x| x.timestamp);
+                    (id, list.into())
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod history_test {
+    use super::*;
+    use crate::test_util::production;
+
+    #[test]
+    fn test_record_and_stats() {
+        let mut history = PriceHistory::default();
+        assert!(history.record(&[production(206, 1000, &[(1, 900), (1, 800)])], 1000));
+        // 间隔内不记录
+        assert!(!history.record(&[production(206, 5000, &[(1, 900)])], 1100));
+        assert!(history.record(&[production(206, 1200, &[(1, 850)])], 1300));
+        assert!(history.record(&[production(206, 1100, &[])], 1600));
+
+        let stats = history.stats(206, PriceField::Market, 3600, 1600).unwrap();
+        assert_eq!(
+            stats,
+            PriceStats {
+                count: 3,
+                avg: 1100,
+                min: 1000,
+                max: 1200,
+                median: 1100,
+            }
+        );
+        let cheapest = history
+            .stats(206, PriceField::Cheapest, 3600, 1600)
+            .unwrap();
+        assert_eq!((cheapest.count, cheapest.median), (2, 825));
+        // 窗口外的数据不统计
+        let recent = history.stats(206, PriceField::Market, 300, 1600).unwrap();
+        assert_eq!((recent.count, recent.avg), (2, 1150));
+        assert!(history.stats(1, PriceField::Market, 3600, 1600).is_none());
+
+        assert_eq!(
+            history.reference_price(206, PriceField::Market, PriceReference::Median(3600), 1600),
+            Some(1100)
+        );
+        assert_eq!(
+            history.reference_price(206, PriceField::Market, PriceReference::Current, 1600),
+            None
+        );
+    }
+
+    #[test]
+    fn test_retention() {
+        let mut history = PriceHistory {
+            retention: HistoryRetention {
+                max_age_sec: 1000,
+                max_snapshots: 2,
+                min_interval_sec: 0,
+            },
+            ..Default::default()
+        };
+        for (i, now) in [100, 200, 300].iter().enumerate() {
+            history.record(&[production(206, 1000 + i as i64, &[])], *now);
+        }
+        let prices: Vec<Option<i64>> = history.snapshots(206).map(|x| x.market_price).collect();
+        assert_eq!(prices, vec![Some(1001), Some(1002)]);
+
+        history.prune(2000);
+        assert_eq!(history.snapshots(206).count(), 0);
+    }
+
+    #[test]
+    fn test_json() {
+        let mut history = PriceHistory::default();
+        history.record(
+            &[production(206, 1000, &[(1, 900)]), production(260, 50, &[])],
+            1000,
+        );
+        let json = history.to_json().unwrap();
+        let loaded = PriceHistory::from_json(&json, HistoryRetention::default()).unwrap();
+        let snapshots: Vec<&PriceSnapshot> = loaded.snapshots(206).collect();
+        assert_eq!(snapshots, history.snapshots(206).collect::<Vec<_>>());
+        assert_eq!(loaded.snapshots(260).next().unwrap().cheapest_price, None);
+        assert!(PriceHistory::from_json("{", HistoryRetention::default()).is_err());
+    }
+
+    #[test]
+    fn test_price_reference() {
+        assert_eq!(
+            PriceReference::parse("median:86400"),
+            PriceReference::Median(86400)
+        );
+        assert_eq!(
+            PriceReference::parse(" Average : 3600 "),
+            PriceReference::Average(3600)
+        );
+        assert_eq!(PriceReference::parse("median"), PriceReference::Current);
+        assert_eq!(PriceReference::parse(""), PriceReference::Current);
+        assert_eq!(PriceReference::Median(60).format(), "median:60");
+    }
+}
@@ -1,3 +1,18 @@
 // pub mod favorite;
 pub mod profit;
+pub mod fee;
+pub mod planner;
+pub mod history;
+pub mod confidence;
+pub mod player;
+pub mod lifecycle;
+pub mod sort;
+pub mod alert;
+pub mod quote;
+pub mod points;
+pub mod failure;
+/// 测试共用的数据构造
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "godot")]
 pub mod data;
\ No newline at end of file
@@ -0,0 +1,341 @@
+use std::collections::{HashMap, VecDeque};
+
+use model::weav3r::favorites::ProductionItem;
+
+/// 挂单标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListingKey {
+    pub player_id: i32,
+    pub id: i32,
+    pub price: u64,
+}
+
+/// 挂单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingStatus {
+    /// 仍在挂单
+    Active,
+    /// 挂单消失，可能已经被买走，也可能被卖家撤下
+    Disappeared,
+    /// 同一卖家同一物品改了价格
+    PriceChanged(u64),
+}
+
+/// 单个挂单的生命周期
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingRecord {
+    pub key: ListingKey,
+    pub player_name: String,
+    pub name: String,
+    /// 最后一次看到的数量
+    pub quantity: i32,
+    /// 第一次看到时相对市场价的折扣百分比
+    pub discount: f32,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// 发现消失或改价的时间
+    pub closed_on: Option<u64>,
+    pub status: ListingStatus,
+    /// 改价前的价格
+    pub previous_price: Option<u64>,
+    /// 第一次拉取时就已经存在，实际出现时间未知，不参与存活时间统计
+    pub is_initial: bool,
+}
+
+impl ListingRecord {
+    /// 存活时间，仍在挂单时为到最后一次看到的时间
+    pub fn lifetime(&self) -> u64 {
+        self.closed_on
+            .unwrap_or(self.last_seen)
+            .saturating_sub(self.first_seen)
+    }
+}
+
+/// 存活时间统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LifetimeStats {
+    /// 统计的挂单数量
+    pub count: usize,
+    /// 存活时间中位数，秒
+    pub median_lifetime: u64,
+    /// 最短存活时间，秒
+    pub min_lifetime: u64,
+    /// 改价次数
+    pub price_changes: usize,
+}
+
+impl LifetimeStats {
+    fn from_records(records: &[&ListingRecord]) -> Self {
+        let mut lifetimes: Vec<u64> = records
+            .iter()
+            .filter(|x| x.status == ListingStatus::Disappeared)
+            .map(|x| x.lifetime())
+            .collect();
+        lifetimes.sort();
+        let count = lifetimes.len();
+        Self {
+            count,
+            median_lifetime: if count == 0 { 0 } else { lifetimes[count / 2] },
+            min_lifetime: lifetimes.first().copied().unwrap_or_default(),
+            price_changes: records
+                .iter()
+                .filter(|x| matches!(x.status, ListingStatus::PriceChanged(_)))
+                .count(),
+        }
+    }
+}
+
+/// 物品维度的存活时间统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemLifetimeStats {
+    pub id: i32,
+    pub name: String,
+    pub stats: LifetimeStats,
+}
+
+/// 折扣区间的存活时间统计，区间为 [discount_from, discount_from + 区间宽度)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscountBandStats {
+    pub discount_from: i32,
+    pub stats: LifetimeStats,
+}
+
+/// 挂单生命周期跟踪，每次拉取后更新
+/// 只能看到每个物品最便宜的几个挂单，挂单被更便宜的挂单挤出列表时也会视为消失
+#[derive(Debug, Clone)]
+pub struct ListingTracker {
+    active: HashMap<ListingKey, ListingRecord>,
+    closed: VecDeque<ListingRecord>,
+    /// 最多保留的已结束挂单数量
+    pub max_closed: usize,
+    initialized: bool,
+}
+
+impl Default for ListingTracker {
+    fn default() -> Self {
+        Self {
+            active: HashMap::new(),
+            closed: VecDeque::new(),
+            max_closed: 5000,
+            initialized: false,
+        }
+    }
+}
+
+impl ListingTracker {
+    /// 根据本次拉取结果更新挂单状态
+    pub fn update(&mut self, productions: &[ProductionItem], now: u64) {
+        let mut current: HashMap<ListingKey, ListingRecord> = HashMap::new();
+        for product in productions.iter() {
+            let market_price = product.market_price.unwrap_or_default();
+            for bazaar in product.cheapest_bazaars.iter() {
+                let price = bazaar.price.max(0) as u64;
+                let key = ListingKey {
+                    player_id: bazaar.player_id,
+                    id: product.id,
+                    price,
+                };
+                let discount = if market_price > 0 {
+                    (market_price - price as i64) as f32 / market_price as f32 * 100.0
+                } else {
+                    0.0
+                };
+                // 同一价格拆开的挂单合并数量
+                current
+                    .entry(key)
+                    .and_modify(|x| x.quantity += bazaar.quantity)
+                    .or_insert_with(|| ListingRecord {
+                        key,
+                        player_name: bazaar.player_name.clone(),
+                        name: product.name.clone(),
+                        quantity: bazaar.quantity,
+                        discount,
+                        first_seen: now,
+                        last_seen: now,
+                        closed_on: None,
+                        status: ListingStatus::Active,
+                        previous_price: None,
+                        is_initial: !self.initialized,
+                    });
+            }
+        }
+
+        // 已消失的挂单，同一卖家同一物品出现新价格时视为改价
+        let mut gone: Vec<ListingKey> = self
+            .active
+            .keys()
+            .filter(|x| !current.contains_key(x))
+            .copied()
+            .collect();
+        gone.sort();
+        for key in gone {
+            let Some(mut record) = self.active.remove(&key) else {
+                continue;
+            };
+            let new_price = current
+                .keys()
+                .filter(|x| {
+                    x.player_id == key.player_id && x.id == key.id && !self.active.contains_key(x)
+                })
+                .map(|x| x.price)
+                .min();
+            record.closed_on = Some(now);
+            record.status = match new_price {
+                Some(new_price) => {
+                    let new_key = ListingKey {
+                        price: new_price,
+                        ..key
+                    };
+                    if let Some(new_record) = current.get_mut(&new_key)
+                        && new_record.previous_price.is_none()
+                    {
+                        new_record.previous_price = Some(key.price);
+                        new_record.is_initial = record.is_initial;
+                    }
+                    ListingStatus::PriceChanged(new_price)
+                }
+                None => ListingStatus::Disappeared,
+            };
+            self.closed.push_back(record);
+        }
+        while self.closed.len() > self.max_closed {
+            self.closed.pop_front();
+        }
+
+        for (key, record) in current {
+            match self.active.get_mut(&key) {
+                Some(active) => {
+                    active.last_seen = now;
+                    active.quantity = record.quantity;
+                }
+                None => {
+                    self.active.insert(key, record);
+                }
+            }
+        }
+        self.initialized = true;
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = &ListingRecord> {
+        self.active.values()
+    }
+
+    pub fn closed(&self) -> impl Iterator<Item = &ListingRecord> {
+        self.closed.iter()
+    }
+
+    /// 参与统计的挂单，排除第一次拉取时就已存在的挂单
+    fn tracked_closed(&self) -> impl Iterator<Item = &ListingRecord> {
+        self.closed.iter().filter(|x| !x.is_initial)
+    }
+
+    /// 按物品统计存活时间，按中位数从短到长排序
+    pub fn item_stats(&self) -> Vec<ItemLifetimeStats> {
+        let mut groups: HashMap<i32, Vec<&ListingRecord>> = HashMap::new();
+        for record in self.tracked_closed() {
+            groups.entry(record.key.id).or_default().push(record);
+        }
+        let mut res: Vec<ItemLifetimeStats> = groups
+            .into_iter()
+            .map(|(id, records)| ItemLifetimeStats {
+                id,
+                name: records[0].name.clone(),
+                stats: LifetimeStats::from_records(&records),
+            })
+            .collect();
+        res.sort_by(|a, b| {
+            a.stats
+                .median_lifetime
+                .cmp(&b.stats.median_lifetime)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        res
+    }
+
+    /// 按折扣区间统计存活时间，band_width 为区间宽度百分比
+    pub fn discount_band_stats(&self, band_width: u32) -> Vec<DiscountBandStats> {
+        let band_width = band_width.max(1) as i32;
+        let mut groups: HashMap<i32, Vec<&ListingRecord>> = HashMap::new();
+        for record in self.tracked_closed() {
+            let band = (record.discount.floor() as i32).div_euclid(band_width) * band_width;
+            groups.entry(band).or_default().push(record);
+        }
+        let mut res: Vec<DiscountBandStats> = groups
+            .into_iter()
+            .map(|(discount_from, records)| DiscountBandStats {
+                discount_from,
+                stats: LifetimeStats::from_records(&records),
+            })
+            .collect();
+        res.sort_by_key(|x| x.discount_from);
+        res
+    }
+}
+
+#[cfg(test)]
+mod lifecycle_test {
+    use super::*;
+    use crate::test_util::production;
+
+    #[test]
+    fn test_lifecycle() {
+        let mut tracker = ListingTracker::default();
+        // 第一次拉取已存在的挂单不参与统计
+        tracker.update(&[production(206, 1000, &[(1, 900)])], 100);
+        tracker.update(&[production(206, 1000, &[(1, 900), (2, 800)])], 110);
+        tracker.update(&[production(206, 1000, &[(2, 800), (3, 950)])], 130);
+        // 玩家 3 改价
+        tracker.update(&[production(206, 1000, &[(3, 940)])], 160);
+        tracker.update(&[production(206, 1000, &[])], 200);
+
+        let closed: Vec<(i32, u64, ListingStatus, u64)> = tracker
+            .closed()
+            .map(|x| (x.key.player_id, x.key.price, x.status, x.lifetime()))
+            .collect();
+        assert_eq!(
+            closed,
+            vec![
+                (1, 900, ListingStatus::Disappeared, 30),
+                (2, 800, ListingStatus::Disappeared, 50),
+                (3, 950, ListingStatus::PriceChanged(940), 30),
+                (3, 940, ListingStatus::Disappeared, 40),
+            ]
+        );
+        assert!(tracker.closed().next().unwrap().is_initial);
+        assert_eq!(tracker.closed().last().unwrap().previous_price, Some(950));
+        assert_eq!(tracker.active().count(), 0);
+
+        let stats = tracker.item_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(
+            stats[0].stats,
+            LifetimeStats {
+                count: 2,
+                median_lifetime: 50,
+                min_lifetime: 40,
+                price_changes: 1,
+            }
+        );
+
+        let bands: Vec<(i32, usize)> = tracker
+            .discount_band_stats(10)
+            .iter()
+            .map(|x| (x.discount_from, x.stats.count))
+            .collect();
+        // 折扣 20% 和 6%(改价后)，改价前的 5% 只计入改价次数
+        assert_eq!(bands, vec![(0, 1), (20, 1)]);
+    }
+
+    #[test]
+    fn test_max_closed() {
+        let mut tracker = ListingTracker {
+            max_closed: 2,
+            ..Default::default()
+        };
+        for i in 0..5 {
+            tracker.update(&[production(206, 1000, &[(i, 900)])], i as u64 * 10);
+        }
+        assert_eq!(tracker.closed().count(), 2);
+        assert_eq!(tracker.active().count(), 1);
+    }
+}
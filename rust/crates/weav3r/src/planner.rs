@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use crate::profit::ProfitInfo;
+
+/// 购买计划参数
+#[derive(Debug, Clone, Default)]
+pub struct PlanParams {
+    /// 可用资金
+    pub budget: u64,
+    /// 单个物品最多购买数量，物品 id -> 数量
+    pub quantity_caps: HashMap<i32, i32>,
+}
+
+impl PlanParams {
+    /// 解析数量限制，格式：`id:数量,id:数量`
+    pub fn parse_quantity_caps(text: &str) -> HashMap<i32, i32> {
+        text.split(',')
+            .filter_map(|x| {
+                let (id, cap) = x.split_once(':')?;
+                Some((
+                    id.trim().parse::<i32>().ok()?,
+                    cap.trim().parse::<i32>().ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// 转换为文本，与 parse_quantity_caps 对应
+    pub fn format_quantity_caps(caps: &HashMap<i32, i32>) -> String {
+        let mut caps: Vec<(&i32, &i32)> = caps.iter().collect();
+        caps.sort();
+        caps.iter()
+            .map(|(id, cap)| format!("{}:{}", id, cap))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// 购买清单中的一项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanEntry {
+    pub player_id: i32,
+    pub player_name: String,
+    pub id: i32,
+    pub name: String,
+    /// 购买数量
+    pub quantity: i32,
+    /// 单个购买价格
+    pub single_price: u64,
+    /// 本项花费
+    pub total_price: u64,
+    /// 本项预期净利润
+    pub total_profit: i64,
+    /// 累计花费
+    pub cash_used: u64,
+    /// 剩余资金
+    pub cash_left: u64,
+    /// 累计预期净利润
+    pub profit_sum: i64,
+}
+
+/// 购买计划
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PurchasePlan {
+    pub budget: u64,
+    pub entries: Vec<PlanEntry>,
+    pub total_cost: u64,
+    pub total_profit: i64,
+}
+
+/// 动态规划的资金格数，资金按 budget / PLAN_UNITS 向上取整分格，价格向上取整，结果不会超出预算
+const PLAN_UNITS: u64 = 2000;
+
+/// 可以购买的挂单和最多购买的数量
+struct Candidate<'a> {
+    item: &'a ProfitInfo,
+    quantity: i32,
+}
+
+/// 在资金限制下挑选购买的挂单和数量，让预期净利润最大
+/// listings 为合并前的单个挂单，同一物品的单个利润只由价格决定，数量限制优先分配给最便宜的挂单，
+/// 然后按资金格做有界背包，取整会损失少量精度，再与按利润率贪心的结果比较取较好的一个
+pub fn plan_purchases(params: &PlanParams, listings: &[ProfitInfo]) -> PurchasePlan {
+    let candidates = candidates(params, listings);
+    let dp = plan_knapsack(params.budget, &candidates);
+    let greedy = plan_greedy(params.budget, &candidates);
+    let profit = |counts: &[i32]| {
+        counts
+            .iter()
+            .zip(candidates.iter())
+            .map(|(count, x)| x.item.final_profit.single_profit_value * *count as i64)
+            .sum::<i64>()
+    };
+    let counts = if profit(&dp) >= profit(&greedy) {
+        dp
+    } else {
+        greedy
+    };
+    build_plan(params.budget, &candidates, &counts)
+}
+
+/// 过滤掉没有利润的挂单，按数量限制截断，按利润率从高到低排列
+fn candidates<'a>(params: &PlanParams, listings: &'a [ProfitInfo]) -> Vec<Candidate<'a>> {
+    let mut items: Vec<&ProfitInfo> = listings
+        .iter()
+        .filter(|x| {
+            x.quantity > 0 && x.single_recyle_price > 0 && x.final_profit.single_profit_value > 0
+        })
+        .collect();
+    // 同一物品先分配便宜的挂单
+    items.sort_by(|a, b| {
+        a.id.cmp(&b.id)
+            .then_with(|| a.single_recyle_price.cmp(&b.single_recyle_price))
+            .then_with(|| a.player_id.cmp(&b.player_id))
+    });
+    let mut left: HashMap<i32, i32> = params.quantity_caps.clone();
+    let mut candidates: Vec<Candidate> = items
+        .into_iter()
+        .filter_map(|item| {
+            let mut quantity = item.quantity;
+            if let Some(left) = left.get_mut(&item.id) {
+                quantity = quantity.min(*left);
+                *left -= quantity.max(0);
+            }
+            (quantity > 0).then_some(Candidate { item, quantity })
+        })
+        .collect();
+
+    // 利润率相同时优先单个利润高的
+    candidates.sort_by(|a, b| {
+        profit_rate(b.item)
+            .total_cmp(&profit_rate(a.item))
+            .then_with(|| {
+                b.item
+                    .final_profit
+                    .single_profit_value
+                    .cmp(&a.item.final_profit.single_profit_value)
+            })
+            .then_with(|| a.item.player_id.cmp(&b.item.player_id))
+            .then_with(|| a.item.id.cmp(&b.item.id))
+            .then_with(|| a.item.single_recyle_price.cmp(&b.item.single_recyle_price))
+    });
+    candidates
+}
+
+/// 按利润率从高到低依次购买，买不起整个挂单时买能买得起的数量
+fn plan_greedy(budget: u64, candidates: &[Candidate]) -> Vec<i32> {
+    let mut cash_left = budget;
+    candidates
+        .iter()
+        .map(|x| {
+            let affordable = (cash_left / x.item.single_recyle_price).min(i32::MAX as u64) as i32;
+            let quantity = x.quantity.min(affordable);
+            cash_left -= x.item.single_recyle_price * quantity as u64;
+            quantity
+        })
+        .collect()
+}
+
+/// 有界背包，每个挂单的数量按二进制拆分成多个 0/1 物品
+fn plan_knapsack(budget: u64, candidates: &[Candidate]) -> Vec<i32> {
+    let mut counts = vec![0; candidates.len()];
+    let unit = budget.div_ceil(PLAN_UNITS).max(1);
+    let capacity = (budget / unit) as usize;
+    if capacity == 0 {
+        return counts;
+    }
+
+    // (挂单下标, 数量, 资金格数, 利润)
+    let mut bundles: Vec<(usize, i32, usize, i64)> = Vec::new();
+    for (i, x) in candidates.iter().enumerate() {
+        let max_count = (budget / x.item.single_recyle_price).min(x.quantity as u64) as i32;
+        let mut left = max_count;
+        let mut size = 1;
+        while left > 0 {
+            let count = size.min(left);
+            let cost = (x.item.single_recyle_price * count as u64).div_ceil(unit);
+            if cost as usize <= capacity {
+                bundles.push((
+                    i,
+                    count,
+                    cost as usize,
+                    x.item.final_profit.single_profit_value * count as i64,
+                ));
+            }
+            left -= count;
+            size = size.saturating_mul(2);
+        }
+    }
+
+    let mut best = vec![0i64; capacity + 1];
+    let mut taken = vec![vec![false; capacity + 1]; bundles.len()];
+    for (b, (_, _, cost, profit)) in bundles.iter().enumerate() {
+        for c in (*cost..=capacity).rev() {
+            let value = best[c - cost] + profit;
+            if value > best[c] {
+                best[c] = value;
+                taken[b][c] = true;
+            }
+        }
+    }
+
+    let mut c = capacity;
+    for (b, (i, count, cost, _)) in bundles.iter().enumerate().rev() {
+        if taken[b][c] {
+            counts[*i] += count;
+            c -= cost;
+        }
+    }
+    counts
+}
+
+/// 按候选顺序生成购买清单，同时计算累计花费和利润
+fn build_plan(budget: u64, candidates: &[Candidate], counts: &[i32]) -> PurchasePlan {
+    let mut plan = PurchasePlan {
+        budget,
+        ..Default::default()
+    };
+    for (x, quantity) in candidates.iter().zip(counts.iter().copied()) {
+        if quantity <= 0 {
+            continue;
+        }
+        let item = x.item;
+        let total_price = item.single_recyle_price * quantity as u64;
+        let total_profit = item.final_profit.single_profit_value * quantity as i64;
+        plan.total_cost += total_price;
+        plan.total_profit += total_profit;
+        plan.entries.push(PlanEntry {
+            player_id: item.player_id,
+            player_name: item.player_name.clone(),
+            id: item.id,
+            name: item.name.clone(),
+            quantity,
+            single_price: item.single_recyle_price,
+            total_price,
+            total_profit,
+            cash_used: plan.total_cost,
+            cash_left: budget - plan.total_cost,
+            profit_sum: plan.total_profit,
+        });
+    }
+    plan
+}
+
+/// 单位资金的利润
+fn profit_rate(item: &ProfitInfo) -> f64 {
+    item.final_profit.single_profit_value as f64 / item.single_recyle_price as f64
+}
+
+#[cfg(test)]
+mod planner_test {
+    use super::*;
+    use crate::test_util::profit_info;
+
+    fn listings() -> Vec<ProfitInfo> {
+        vec![
+            profit_info(1, 206, 10, 1000, 100, 0.0),
+            profit_info(1, 260, 5, 100, 50, 0.0),
+            profit_info(2, 206, 3, 900, 200, 0.0),
+            profit_info(2, 186, 1, 50, -10, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_plan_purchases() {
+        let params = PlanParams {
+            budget: 5000,
+            ..Default::default()
+        };
+        let plan = plan_purchases(&params, &listings());
+        let res: Vec<(i32, i32, i32)> = plan
+            .entries
+            .iter()
+            .map(|x| (x.player_id, x.id, x.quantity))
+            .collect();
+        // 260 利润率 50%，206(玩家2) 22%，206(玩家1) 10%
+        assert_eq!(res, vec![(1, 260, 5), (2, 206, 3), (1, 206, 1)]);
+        assert_eq!(plan.total_cost, 500 + 2700 + 1000);
+        assert_eq!(plan.total_profit, 250 + 600 + 100);
+
+        let last = plan.entries.last().unwrap();
+        assert_eq!(last.cash_used, plan.total_cost);
+        assert_eq!(last.cash_left, 5000 - plan.total_cost);
+        assert_eq!(last.profit_sum, plan.total_profit);
+    }
+
+    #[test]
+    fn test_plan_quantity_caps() {
+        let params = PlanParams {
+            budget: 100_000,
+            quantity_caps: PlanParams::parse_quantity_caps("206:4, 260:0"),
+        };
+        let plan = plan_purchases(&params, &listings());
+        let res: Vec<(i32, i32, i32)> = plan
+            .entries
+            .iter()
+            .map(|x| (x.player_id, x.id, x.quantity))
+            .collect();
+        assert_eq!(res, vec![(2, 206, 3), (1, 206, 1)]);
+        assert_eq!(
+            PlanParams::format_quantity_caps(&params.quantity_caps),
+            "206:4,260:0"
+        );
+    }
+
+    #[test]
+    fn test_plan_better_than_greedy() {
+        // 按利润率贪心会先买便宜的挂单，剩下的钱买不起利润更高的大单
+        let params = PlanParams {
+            budget: 1000,
+            ..Default::default()
+        };
+        let listings = vec![
+            profit_info(1, 206, 1, 100, 60, 0.0),
+            profit_info(2, 260, 1, 1000, 500, 0.0),
+        ];
+        let plan = plan_purchases(&params, &listings);
+        assert_eq!(plan.total_profit, 500);
+        assert_eq!(plan.total_cost, 1000);
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].player_id, 2);
+
+        // 同一卖家同一物品的多个挂单，预算不够时先买便宜的，数量限制也先分配给便宜的
+        let params = PlanParams {
+            budget: 250,
+            quantity_caps: PlanParams::parse_quantity_caps("206:2"),
+        };
+        let listings = vec![
+            profit_info(1, 206, 2, 150, 50, 0.0),
+            profit_info(1, 206, 2, 100, 100, 0.0),
+        ];
+        let plan = plan_purchases(&params, &listings);
+        let res: Vec<(u64, i32)> = plan
+            .entries
+            .iter()
+            .map(|x| (x.single_price, x.quantity))
+            .collect();
+        assert_eq!(res, vec![(100, 2)]);
+        assert_eq!(plan.total_profit, 200);
+    }
+}
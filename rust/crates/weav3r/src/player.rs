@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+/// 玩家标记
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlayerMark {
+    #[default]
+    None,
+    /// 屏蔽，过滤时直接丢弃
+    Blocked,
+    /// 收藏，排序时优先
+    Favorite,
+}
+
+impl PlayerMark {
+    pub fn parse(mark: &str) -> Self {
+        match mark.trim().to_lowercase().as_str() {
+            "block" | "blocked" => PlayerMark::Blocked,
+            "favorite" | "fav" => PlayerMark::Favorite,
+            _ => PlayerMark::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerMark::None => "",
+            PlayerMark::Blocked => "block",
+            PlayerMark::Favorite => "favorite",
+        }
+    }
+}
+
+/// 单个玩家的标记和备注
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerRule {
+    pub player_id: i32,
+    pub mark: PlayerMark,
+    pub note: String,
+}
+
+/// 玩家列表，player_id -> 标记和备注
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerList {
+    pub rules: HashMap<i32, PlayerRule>,
+}
+
+impl PlayerList {
+    pub fn get(&self, player_id: i32) -> Option<&PlayerRule> {
+        self.rules.get(&player_id)
+    }
+
+    pub fn is_blocked(&self, player_id: i32) -> bool {
+        self.get(player_id)
+            .is_some_and(|x| x.mark == PlayerMark::Blocked)
+    }
+
+    pub fn is_favorite(&self, player_id: i32) -> bool {
+        self.get(player_id)
+            .is_some_and(|x| x.mark == PlayerMark::Favorite)
+    }
+
+    pub fn note(&self, player_id: i32) -> &str {
+        self.get(player_id).map(|x| x.note.as_str()).unwrap_or("")
+    }
+
+    /// 设置标记，没有标记也没有备注时删除
+    pub fn set_mark(&mut self, player_id: i32, mark: PlayerMark) {
+        let rule = self.rules.entry(player_id).or_insert_with(|| PlayerRule {
+            player_id,
+            ..Default::default()
+        });
+        rule.mark = mark;
+        self.remove_empty(player_id);
+    }
+
+    /// 设置备注，没有标记也没有备注时删除
+    pub fn set_note(&mut self, player_id: i32, note: &str) {
+        let rule = self.rules.entry(player_id).or_insert_with(|| PlayerRule {
+            player_id,
+            ..Default::default()
+        });
+        rule.note = note.trim().replace('\n', " ");
+        self.remove_empty(player_id);
+    }
+
+    fn remove_empty(&mut self, player_id: i32) {
+        if self
+            .get(player_id)
+            .is_some_and(|x| x.mark == PlayerMark::None && x.note.is_empty())
+        {
+            self.rules.remove(&player_id);
+        }
+    }
+
+    /// 从文本解析，每行一个：`player_id,block/favorite,备注`
+    /// 标记为空表示只有备注，备注中可以包含逗号
+    pub fn parse_lines(text: &str) -> PlayerList {
+        let mut list = PlayerList::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(3, ',').map(|x| x.trim());
+            let Some(player_id) = parts.next().and_then(|x| x.parse::<i32>().ok()) else {
+                log::warn!("PlayerList: invalid player id, line: {}", line);
+                continue;
+            };
+            let mark = parts.next().map(PlayerMark::parse).unwrap_or_default();
+            let note = parts.next().unwrap_or_default();
+            list.set_mark(player_id, mark);
+            list.set_note(player_id, note);
+        }
+        list
+    }
+
+    /// 转换为文本，与 parse_lines 对应
+    pub fn format_lines(&self) -> String {
+        let mut rules: Vec<&PlayerRule> = self.rules.values().collect();
+        rules.sort_by_key(|x| x.player_id);
+        rules
+            .iter()
+            .map(|x| format!("{},{},{}", x.player_id, x.mark.as_str(), x.note))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod player_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let list = PlayerList::parse_lines(
+            "100,block,bait seller\n200,favorite,\n300,,fast, reliable\nabc,block\n400,,",
+        );
+        assert!(list.is_blocked(100));
+        assert!(list.is_favorite(200));
+        assert!(!list.is_blocked(300) && !list.is_favorite(300));
+        assert_eq!(list.note(300), "fast, reliable");
+        assert_eq!(list.note(1), "");
+        assert_eq!(list.rules.len(), 3);
+        assert_eq!(PlayerList::parse_lines(&list.format_lines()), list);
+    }
+
+    #[test]
+    fn test_set_mark() {
+        let mut list = PlayerList::default();
+        list.set_mark(100, PlayerMark::Favorite);
+        list.set_note(100, "note\nline");
+        assert_eq!(list.note(100), "note line");
+        list.set_mark(100, PlayerMark::None);
+        assert!(list.get(100).is_some());
+        list.set_note(100, "");
+        assert!(list.get(100).is_none());
+    }
+}
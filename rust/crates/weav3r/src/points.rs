@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use model::{
+    error::MyError,
+    torn::{api::parse_torn_response, points_market::PointsMarketResponse},
+};
+use serde::{Deserialize, Serialize};
+
+/// 某个时间点的点数价格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointsPrice {
+    #[serde(rename = "p")]
+    pub price: u64,
+    #[serde(rename = "t")]
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PointsPriceFile {
+    version: u32,
+    history: Vec<PointsPrice>,
+}
+
+/// 点数价格缓存，保存最新价格和历史价格
+#[derive(Debug, Clone)]
+pub struct PointsPriceCache {
+    history: VecDeque<PointsPrice>,
+    /// 最多保留的历史数量
+    pub max_history: usize,
+    /// 价格有效时间，超过后视为没有价格
+    pub max_age_sec: u64,
+}
+
+impl Default for PointsPriceCache {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::new(),
+            max_history: 2016,
+            max_age_sec: 3600,
+        }
+    }
+}
+
+impl PointsPriceCache {
+    pub const FILE_VERSION: u32 = 1;
+    pub const CACHE_PATH: &str = "user://points_price.json";
+
+    /// 解析点数市场响应，使用最低挂单价作为当前价格
+    /// 先解析错误结构，状态码不是 200 的错误响应也能识别出限流等错误
+    pub fn parse_price(response_code: i64, json: &str, now: u64) -> Result<PointsPrice, MyError> {
+        let response = parse_torn_response::<PointsMarketResponse>(response_code, json)?;
+        let price = response
+            .lowest_cost()
+            .ok_or_else(|| MyError::JsonParse("points market is empty".to_string()))?;
+        Ok(PointsPrice {
+            price,
+            timestamp: now,
+        })
+    }
+
+    /// 记录新的价格，时间早于最新记录时忽略
+    pub fn record(&mut self, price: PointsPrice) -> bool {
+        if let Some(latest) = self.latest()
+            && price.timestamp < latest.timestamp
+        {
+            return false;
+        }
+        self.history.push_back(price);
+        while self.history.len() > self.max_history {
+            self.history.pop_front();
+        }
+        true
+    }
+
+    pub fn latest(&self) -> Option<PointsPrice> {
+        self.history.back().copied()
+    }
+
+    /// 未过期的当前价格
+    pub fn current_price(&self, now: u64) -> Option<u64> {
+        self.latest()
+            .filter(|x| now.saturating_sub(x.timestamp) <= self.max_age_sec)
+            .map(|x| x.price)
+    }
+
+    /// 是否需要重新拉取
+    pub fn is_stale(&self, now: u64, refresh_sec: u64) -> bool {
+        self.latest()
+            .is_none_or(|x| now.saturating_sub(x.timestamp) >= refresh_sec)
+    }
+
+    /// since 之后的历史价格，按时间排序
+    pub fn history(&self, since: u64) -> impl Iterator<Item = &PointsPrice> {
+        self.history.iter().filter(move |x| x.timestamp >= since)
+    }
+
+    pub fn to_json(&self) -> Result<String, MyError> {
+        let file = PointsPriceFile {
+            version: Self::FILE_VERSION,
+            history: self.history.iter().copied().collect(),
+        };
+        serde_json::to_string(&file).map_err(|e| MyError::JsonParse(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MyError> {
+        let mut file: PointsPriceFile =
+            serde_json::from_str(json).map_err(|e| MyError::JsonParse(e.to_string()))?;
+        file.history.sort_by_key(|x| x.timestamp);
+        let mut res = Self::default();
+        for price in file.history {
+            res.record(price);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod points_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_price() {
+        let json = include_str!("../fixtures/torn_points_market.json");
+        let price = PointsPriceCache::parse_price(200, json, 100).unwrap();
+        assert_eq!(
+            price,
+            PointsPrice {
+                price: 31700,
+                timestamp: 100
+            }
+        );
+        assert!(PointsPriceCache::parse_price(200, r#"{"pointsmarket":{}}"#, 100).is_err());
+        assert!(PointsPriceCache::parse_price(200, "[]", 100).is_err());
+
+        // 错误响应的状态码不是 200 时也按错误码处理
+        let busy = r#"{"error":{"code":5,"error":"Too many requests"}}"#;
+        assert!(matches!(
+            PointsPriceCache::parse_price(429, busy, 100),
+            Err(MyError::TornTooManyRequests(_))
+        ));
+        assert!(matches!(
+            PointsPriceCache::parse_price(502, "bad gateway", 100),
+            Err(MyError::NetworkCode(502, _))
+        ));
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = PointsPriceCache {
+            max_history: 2,
+            max_age_sec: 60,
+            ..Default::default()
+        };
+        assert_eq!(cache.current_price(0), None);
+        assert!(cache.is_stale(0, 300));
+
+        let price = |price: u64, timestamp: u64| PointsPrice { price, timestamp };
+        assert!(cache.record(price(31000, 100)));
+        assert!(cache.record(price(32000, 200)));
+        // 过期数据不覆盖最新价格
+        assert!(!cache.record(price(30000, 150)));
+        assert!(cache.record(price(33000, 300)));
+        assert_eq!(cache.history(0).count(), 2);
+        assert_eq!(cache.current_price(350), Some(33000));
+        assert_eq!(cache.current_price(361), None);
+        assert!(!cache.is_stale(350, 300));
+
+        let restored = PointsPriceCache::from_json(&cache.to_json().unwrap()).unwrap();
+        let prices: Vec<u64> = restored.history(0).map(|x| x.price).collect();
+        assert_eq!(prices, vec![32000, 33000]);
+    }
+}
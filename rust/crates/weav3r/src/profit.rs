@@ -1,36 +1,142 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use godot::global::godot_error;
 use model::{items::ItemInfo, weav3r::favorites::ProductionItem};
 use tools::order_change::{ContentHash, ContentHashable, hash::StableHasher};
+use torn_logic::{item::EquipmentKind, museum};
+
+use crate::{
+    alert::{AlertAction, AlertEngine, AlertEvent},
+    confidence::DealConfidence,
+    fee::{ChannelFee, ChannelFees, FeeSchedule, SellChannel},
+    history::{PriceField, PriceHistory, PriceReference},
+    lifecycle::ListingTracker,
+    player::PlayerList,
+    points::PointsPriceCache,
+    quote::{PriceQuote, QuoteSource},
+    sort::{self, SortKey},
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct FavoritesData {
     pub filter: Filter,
     pub sort: SortProfitParams,
+    /// 出售费用
+    pub fee: FeeSchedule,
+    /// 本地价格历史
+    pub history: PriceHistory,
+    /// 利润计算使用的参考价格
+    pub price_reference: PriceReference,
+    /// 挂单生命周期
+    pub lifecycle: ListingTracker,
+    /// Torn 物品市场的最新报价
+    pub item_market: HashMap<i32, PriceQuote>,
+    /// 拉取到的点数价格
+    pub points: PointsPriceCache,
+    /// 手动设置的点数价格，没有拉取到点数价格时使用，为 0 时不计算博物馆利润
+    pub points_price: u64,
+    /// 博物馆套装物品的边际价值，每次计算前更新
+    pub museum_values: HashMap<i32, u64>,
+    /// 提醒规则
+    pub alerts: AlertEngine,
+    /// 本次触发的提醒
+    pub alert_events: Vec<AlertEvent>,
+    /// 被提醒规则置顶的卖家，卖家消失后取消置顶
+    pub pinned: HashSet<i32>,
     pub profit_items_new: Vec<ProfitInfo>,
     pub profit_items_old: Vec<ProfitInfo>,
     pub user_profit_result: Vec<ProfitUserInfo>,
+    /// 通过过滤的单个挂单，未合并，用于购买计划
+    pub plan_listings: Vec<ProfitInfo>,
     /// 是否有新增用户
     pub has_new: bool,
 }
 
 impl FavoritesData {
+    /// 物品市场报价的有效时间，超过后不再参与计算
+    pub const ITEM_MARKET_MAX_AGE_SEC: u64 = 900;
+    /// 没有库存数据，每种套装物品按缺少一个计算，最多一个计入博物馆渠道
+    pub const MUSEUM_QUANTITY: i32 = 1;
+
+    /// 接收统一格式的报价，bazaar 报价重新计算利润，物品市场报价在下次计算时使用
+    pub fn set_quotes(&mut self, quotes: Vec<PriceQuote>) {
+        let (bazaar, item_market): (Vec<PriceQuote>, Vec<PriceQuote>) = quotes
+            .into_iter()
+            .partition(|x| x.source == QuoteSource::Weav3rBazaar);
+        for quote in item_market {
+            self.item_market.insert(quote.item_id, quote);
+        }
+        if !bazaar.is_empty() {
+            self.set_new_profit(bazaar.iter().map(|x| x.to_production()).collect());
+        }
+    }
+
+    /// 当前点数价格，优先使用拉取到的价格
+    pub fn current_points_price(&self, now: u64) -> u64 {
+        self.points.current_price(now).unwrap_or(self.points_price)
+    }
+
+    /// 按当前最便宜的挂单计算博物馆套装中每个物品的边际价值
+    fn museum_values(points_price: u64, productions: &[ProductionItem]) -> HashMap<i32, u64> {
+        if points_price == 0 {
+            return HashMap::new();
+        }
+        let cheapest: HashMap<i32, u64> = productions
+            .iter()
+            .filter_map(|x| {
+                let price = x
+                    .cheapest_bazaars
+                    .iter()
+                    .map(|b| b.price.max(0) as u64)
+                    .min()?;
+                Some((x.id, price))
+            })
+            .collect();
+        museum::MUSEUM_SETS
+            .iter()
+            .flat_map(|set| set.valuation(points_price, &cheapest).pieces)
+            .filter_map(|x| Some((x.item_id, x.marginal_value?)))
+            .filter(|x| x.1 > 0)
+            .collect()
+    }
+
+    /// 未过期的物品市场最低挂单价格
+    fn item_market_ask(&self, id: i32, now: u64) -> Option<u64> {
+        self.item_market
+            .get(&id)
+            .filter(|x| now.saturating_sub(x.fetched_at) <= Self::ITEM_MARKET_MAX_AGE_SEC)
+            .and_then(|x| x.lowest_ask())
+    }
+
     pub fn set_new_profit(&mut self, productions: Vec<ProductionItem>) {
+        let now = tools::time::get_current_time();
+        self.lifecycle.update(&productions, now);
+        self.museum_values = Self::museum_values(self.current_points_price(now), &productions);
         let profit_items: Vec<ProfitInfo> = productions
             .iter()
             .flat_map(|x| self.product_to_profit_info(x.clone()))
             .collect();
+        let profit_items = Self::filter_listing_price(profit_items, &self.filter);
+        let listings = profit_items.clone();
         let profit_items = Self::combine(profit_items);
         let profit_items = Self::filter(profit_items, self.filter.clone());
-        let user_profit_result = Self::calc_user_profit(profit_items.clone());
+        let kept: HashSet<(i32, i32)> = profit_items.iter().map(|x| (x.player_id, x.id)).collect();
+        let plan_listings = listings
+            .into_iter()
+            .filter(|x| kept.contains(&(x.player_id, x.id)))
+            .collect();
+        let mut user_profit_result = Self::calc_user_profit(profit_items.clone());
+        Self::mark_players(&self.filter.players, &mut user_profit_result);
         let (user_profit_result, has_new) =
             Self::diff_user_profit(&self.user_profit_result, user_profit_result);
+        self.alert_events = self.alerts.evaluate(&user_profit_result, now);
+        let user_profit_result = self.pin_users(user_profit_result);
         let user_profit_result = Self::sort_profit(self.sort.clone(), user_profit_result);
+        let user_profit_result = Self::pinned_first(user_profit_result);
 
         self.profit_items_old = self.profit_items_new.clone();
         self.profit_items_new = profit_items;
         self.user_profit_result = user_profit_result;
+        self.plan_listings = plan_listings;
         self.has_new = has_new;
     }
 
@@ -51,7 +157,45 @@ impl FavoritesData {
             return res;
         }
 
+        // 有历史数据时使用历史参考价格，避免被短时间的价格波动影响
+        let now = tools::time::get_current_time();
+        let market_price = self
+            .history
+            .reference_price(product.id, PriceField::Market, self.price_reference, now)
+            .unwrap_or(market_price);
+        let avg_bazaar_price = self
+            .history
+            .reference_price(product.id, PriceField::AvgBazaar, self.price_reference, now)
+            .unwrap_or(avg_bazaar_price);
+        // 物品市场有更低的挂单时，卖到市场只能按最低挂单价出售
+        let item_market_ask = self.item_market_ask(product.id, now);
+        let market_price = item_market_ask.map_or(market_price, |ask| market_price.min(ask as i64));
+
+        let office_item = self.filter.office_item_map.get(&product.id);
+        let equipment = office_item.and_then(EquipmentKind::of);
+        // 装备只按基础价值计算：市场价、bazaar 均价和目录价值中最低的一个
+        let (market_price, avg_bazaar_price) = match (equipment, self.filter.equipment_mode) {
+            (None, _) => (market_price, avg_bazaar_price),
+            (Some(_), EquipmentMode::Exclude) => return res,
+            (Some(_), EquipmentMode::BaseValue) => {
+                let base = office_item
+                    .map(|x| x.market_value as i64)
+                    .filter(|x| *x > 0)
+                    .map_or(market_price, |x| x.min(market_price))
+                    .min(avg_bazaar_price);
+                (base, base)
+            }
+        };
+
         let in_target_ids = self.filter.target_ids.contains(&product.id);
+        let item_type = office_item.map(|item| item.t.as_str());
+        let fees = self.fee.fees(item_type);
+        let confidence = DealConfidence::evaluate(
+            market_price,
+            avg_bazaar_price,
+            self.filter.office_item_map.get(&product.id),
+            product.cheapest_bazaars.len(),
+        );
 
         let office_sell_price = if !in_target_ids {
             self.filter
@@ -62,65 +206,129 @@ impl FavoritesData {
             None
         };
 
-        for user_bazaar in product.cheapest_bazaars.iter() {
+        // 每套只需要每种物品一个，博物馆渠道只用于最便宜的挂单中补齐套装的数量
+        let mut museum_left = if self.museum_values.contains_key(&product.id) {
+            Self::MUSEUM_QUANTITY
+        } else {
+            0
+        };
+        let mut bazaars: Vec<_> = product.cheapest_bazaars.iter().collect();
+        bazaars.sort_by_key(|x| x.price);
+        for user_bazaar in bazaars {
             let user_price = user_bazaar.price;
+            // 可以兑换博物馆的部分和剩余部分分开计算
+            let museum_quantity = user_bazaar.quantity.clamp(0, museum_left);
+            museum_left -= museum_quantity;
+            let chunks = [
+                (museum_quantity, true),
+                (user_bazaar.quantity - museum_quantity, false),
+            ];
+            for (quantity, with_museum) in chunks {
+                if quantity <= 0 {
+                    continue;
+                }
+                let mut selected = compute_profit(
+                    in_target_ids,
+                    user_price as u64,
+                    quantity,
+                    market_price,
+                    avg_bazaar_price,
+                    office_sell_price,
+                    fees,
+                );
+                // 博物馆兑换不收取出售费用，利润更高时使用博物馆渠道
+                let museum = self
+                    .museum_values
+                    .get(&product.id)
+                    .filter(|_| with_museum)
+                    .map(|price| ProfitMetrics {
+                        is_museum: true,
+                        ..ProfitMetrics::new(
+                            *price,
+                            &ChannelFee::default(),
+                            user_price as u64,
+                            quantity,
+                            false,
+                        )
+                    });
+                if let Some(museum) = &museum
+                    && selected
+                        .final_profit
+                        .as_ref()
+                        .is_none_or(|x| museum.percentage > x.percentage)
+                {
+                    selected.final_profit = Some(museum.clone());
+                }
+                let Some(final_profit) = selected.final_profit else {
+                    continue;
+                };
 
-            let selected = compute_profit(
-                in_target_ids,
-                user_price as u64,
-                user_bazaar.quantity,
-                market_price,
-                avg_bazaar_price,
-                office_sell_price,
-            );
-            let Some(final_profit) = selected.final_profit else {
-                continue;
-            };
-
-            let profit_info = ProfitInfo {
-                player_id: user_bazaar.player_id,
-                player_name: user_bazaar.player_name.clone(),
-                quantity: user_bazaar.quantity,
-                single_recyle_price: user_bazaar.price as u64,
-                image: product.image.clone(),
-                market_profit: selected.market,
-                avg_bazaar_profit: selected.bazaar,
-                office_profit: selected.office,
-                final_profit,
-                id: product.id,
-                name: product.name.clone(),
-                ..Default::default()
-            };
-            res.push(profit_info);
+                let profit_info = ProfitInfo {
+                    player_id: user_bazaar.player_id,
+                    player_name: user_bazaar.player_name.clone(),
+                    quantity,
+                    single_recyle_price: user_bazaar.price as u64,
+                    total_recyle_value: user_bazaar.price as u64 * quantity as u64,
+                    image: product.image.clone(),
+                    market_profit: selected.market,
+                    avg_bazaar_profit: selected.bazaar,
+                    office_profit: selected.office,
+                    museum_profit: museum,
+                    final_profit,
+                    confidence: confidence.clone(),
+                    item_type: item_type.unwrap_or_default().to_string(),
+                    item_market_ask,
+                    equipment,
+                    id: product.id,
+                    name: product.name.clone(),
+                    ..Default::default()
+                };
+                res.push(profit_info);
+            }
         }
         res
     }
 
-    /// 将相同用户下相同商品的 id 组合在一起，如果价格不一致，用平均值处理
+    /// 将相同用户下相同商品的 id 组合在一起，如果价格不一致，按数量加权处理
     /// 部分商品是拆开售卖的，这边利润计算进行统一处理
     fn combine(data: Vec<ProfitInfo>) -> Vec<ProfitInfo> {
-        let mut data_map: HashMap<(i32, i32), ProfitInfo> = HashMap::new();
-        for ele in data.iter() {
-            match data_map.get(&(ele.player_id, ele.id)) {
-                Some(r) => {
-                    let r = r.combine_other(ele);
-                    data_map.insert((ele.player_id, ele.id), r);
-                }
-                None => {
-                    data_map.insert((ele.player_id, ele.id), ele.clone());
-                }
-            }
+        let mut data_map: HashMap<(i32, i32), Vec<ProfitInfo>> = HashMap::new();
+        for ele in data.into_iter() {
+            data_map
+                .entry((ele.player_id, ele.id))
+                .or_default()
+                .push(ele);
         }
-        data_map.into_values().collect()
+        data_map
+            .into_values()
+            .filter_map(|x| ProfitInfo::combine_all(&x))
+            .collect()
+    }
+
+    /// 单个物品最高购买价格，在合并前按单个挂单过滤
+    fn filter_listing_price(data: Vec<ProfitInfo>, filter: &Filter) -> Vec<ProfitInfo> {
+        data.into_iter()
+            .filter(|item| {
+                filter
+                    .get_filter_item(item.id)
+                    .is_none_or(|x| x.price == 0 || item.single_recyle_price <= x.price)
+            })
+            .collect()
     }
 
     /// 用户维度，过滤利润信息
+    /// 单个物品配置了过滤条件时优先使用，否则使用全局条件
     fn filter(data: Vec<ProfitInfo>, filter: Filter) -> Vec<ProfitInfo> {
         let mut items = Vec::new();
         for item in data.iter() {
             if filter.ignore_names.contains(&item.name) {
                 continue;
             }
+            if filter.players.is_blocked(item.player_id) {
+                continue;
+            }
+
+            let filter_item = filter.get_filter_item(item.id);
 
             let in_target = filter.target_ids.contains(&item.id);
 
@@ -138,8 +346,13 @@ impl FavoritesData {
                 continue;
             }
 
-            if item.final_profit.percentage >= filter.min_profit_percentage
+            let min_profit_percentage = filter_item
+                .and_then(|x| x.profit_percentage)
+                .unwrap_or(filter.min_profit_percentage);
+
+            if item.final_profit.percentage >= min_profit_percentage
                 && item.final_profit.total_profit_value >= filter.min_profit
+                && item.confidence.score >= filter.min_confidence
             {
                 items.push(item.clone());
             }
@@ -194,11 +407,41 @@ impl FavoritesData {
             } else {
                 res.total_profit_price as f32 / res.total_recyle_price as f32 * 100.0
             };
+            res.confidence = ProfitUserInfo::weighted_confidence(&res.items);
         }
 
         user_profit_result
     }
 
+    /// 填充玩家收藏标记和备注
+    fn mark_players(players: &PlayerList, items: &mut [ProfitUserInfo]) {
+        for item in items.iter_mut() {
+            item.is_favorite = players.is_favorite(item.player_id);
+            item.note = players.note(item.player_id).to_string();
+        }
+    }
+
+    /// 记录提醒规则要求置顶的卖家，并标记到结果中
+    fn pin_users(&mut self, mut items: Vec<ProfitUserInfo>) -> Vec<ProfitUserInfo> {
+        for event in self.alert_events.iter() {
+            if event.actions.contains(&AlertAction::Pin) {
+                self.pinned.insert(event.player_id);
+            }
+        }
+        self.pinned
+            .retain(|id| items.iter().any(|x| x.player_id == *id));
+        for item in items.iter_mut() {
+            item.is_pinned = self.pinned.contains(&item.player_id);
+        }
+        items
+    }
+
+    /// 置顶的卖家排在最前面，不改变其他顺序
+    fn pinned_first(mut items: Vec<ProfitUserInfo>) -> Vec<ProfitUserInfo> {
+        items.sort_by_key(|x| !x.is_pinned);
+        items
+    }
+
     /// 新老数据比较，给新的用户增加时间戳，老用户不改变时间戳，返回是否有新增用户
     fn diff_user_profit(
         old: &[ProfitUserInfo],
@@ -217,35 +460,20 @@ impl FavoritesData {
         (new, has_new)
     }
 
-    /// 按利润排序，前 sec 秒的利润排序，然后是老的利润排序
-    fn sort_profit(params: SortProfitParams, items: Vec<ProfitUserInfo>) -> Vec<ProfitUserInfo> {
+    /// 按排序条件排序卖家，卖家的物品也按同样的条件排序
+    fn sort_profit(
+        params: SortProfitParams,
+        mut items: Vec<ProfitUserInfo>,
+    ) -> Vec<ProfitUserInfo> {
         let now = tools::time::get_current_time();
-        let recent_sec = now - params.recent_sec;
-        let mut recent_items: Vec<ProfitUserInfo> = items
-            .clone()
-            .into_iter()
-            .filter(|x| x.created_on >= recent_sec)
-            .collect();
-        recent_items.sort_by(|a, b| b.total_profit_price.cmp(&a.total_profit_price));
-
-        let mut old_items: Vec<ProfitUserInfo> = items
-            .clone()
-            .into_iter()
-            .filter(|x| x.created_on < recent_sec)
-            .collect();
-        old_items.sort_by(|a, b| b.total_profit_price.cmp(&a.total_profit_price));
-
-        // 子项中按利润排序
-        recent_items.extend(old_items);
-        recent_items.iter_mut().for_each(|x| {
-            x.items.sort_by(|a, b| {
-                b.final_profit
-                    .total_profit_value
-                    .cmp(&a.final_profit.total_profit_value)
-            })
+        let recent_since = now.saturating_sub(params.recent_sec);
+        // 子项先排序，卖家按物品类型排序时使用第一个物品
+        items.iter_mut().for_each(|x| {
+            x.items
+                .sort_by(|a, b| sort::compare_items(&params.keys, a, b))
         });
-
-        recent_items
+        items.sort_by(|a, b| sort::compare_users(&params.keys, recent_since, a, b));
+        items
     }
 }
 
@@ -257,18 +485,49 @@ pub struct ProfitUserInfo {
     pub total_recyle_price: u64,
     pub total_profit_price: i64,
     pub profit_percentage: f32,
+    /// 按利润加权的平均可信度
+    pub confidence: f32,
+    /// 是否是收藏的玩家
+    pub is_favorite: bool,
+    /// 是否被提醒规则置顶
+    pub is_pinned: bool,
+    /// 玩家备注
+    pub note: String,
     pub created_on: u64, // 拉取到的时间戳
     pub items: Vec<ProfitInfo>,
 }
 
+impl ProfitUserInfo {
+    /// 按净利润加权计算可信度，没有正利润时取平均值
+    fn weighted_confidence(items: &[ProfitInfo]) -> f32 {
+        if items.is_empty() {
+            return 0.0;
+        }
+        let total_weight = items
+            .iter()
+            .map(|x| x.final_profit.total_profit_value.max(0) as f64)
+            .sum::<f64>();
+        if total_weight <= 0.0 {
+            return items.iter().map(|x| x.confidence.score).sum::<f32>() / items.len() as f32;
+        }
+        (items
+            .iter()
+            .map(|x| x.confidence.score as f64 * x.final_profit.total_profit_value.max(0) as f64)
+            .sum::<f64>()
+            / total_weight) as f32
+    }
+}
+
 /// 商品维度 利润信息
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct ProfitInfo {
     pub player_id: i32,
     pub player_name: String,
     pub quantity: i32,
-    /// 单个回收价格
+    /// 单个回收价格，合并挂单后为加权平均价
     pub single_recyle_price: u64,
+    /// 回收总价格，合并挂单时直接累加，不受单价取整影响
+    pub total_recyle_value: u64,
     pub image: String,
     /// 市场利润
     pub market_profit: ProfitMetrics,
@@ -276,8 +535,18 @@ pub struct ProfitInfo {
     pub avg_bazaar_profit: ProfitMetrics,
     /// 官方利润
     pub office_profit: Option<ProfitMetrics>,
+    /// 博物馆兑换利润，只有套装物品并且设置了点数价格时存在
+    pub museum_profit: Option<ProfitMetrics>,
     // 按照最低的数据进行复制
     pub final_profit: ProfitMetrics,
+    /// 交易可信度
+    pub confidence: DealConfidence,
+    /// 物品类型(ItemInfo.t)
+    pub item_type: String,
+    /// 物品市场最低挂单价格
+    pub item_market_ask: Option<u64>,
+    /// 装备类型，存在时利润只按基础价值计算
+    pub equipment: Option<EquipmentKind>,
 
     pub id: i32,
     pub name: String,
@@ -287,71 +556,116 @@ pub struct ProfitInfo {
 impl ProfitInfo {
     /// 商品回收总价格
     pub fn total_recyle_price(&self) -> u64 {
-        self.quantity as u64 * self.single_recyle_price
+        self.total_recyle_value
     }
 
     /// 商品最终总售价
     pub fn total_sell_price(&self) -> u64 {
-        self.quantity as u64 * self.final_profit.single_sell_price
+        self.final_profit.total_sell_price
     }
 }
 
 impl ProfitInfo {
-    /// 计算单个的利润
-    fn single_value(&self) -> ProfitInfo {
-        let mut cp = self.clone();
-        cp.market_profit = cp.market_profit.single_value(self.quantity);
-        cp.avg_bazaar_profit = cp.avg_bazaar_profit.single_value(self.quantity);
-        cp.final_profit = cp.final_profit.single_value(self.quantity);
-        if let Some(office) = cp.office_profit {
-            cp.office_profit = Some(office.single_value(self.quantity));
+    /// 合并同一用户同一物品的多个挂单，按数量加权
+    /// 总价和总利润直接累加，单价、单个利润和百分比由总数计算，结果与合并顺序无关
+    fn combine_all(data: &[ProfitInfo]) -> Option<ProfitInfo> {
+        let first = data.first()?;
+        let data: Vec<&ProfitInfo> = data
+            .iter()
+            .filter(|x| {
+                if x.id != first.id {
+                    log::error!("ProfitInfo id not equal:{}:{}", first.id, x.id);
+                    return false;
+                }
+                x.quantity > 0
+            })
+            .collect();
+        if data.len() <= 1 {
+            return data.first().map(|x| (*x).clone());
         }
-        cp
-    }
 
-    /// 组合相同的类型
-    fn combine_other(&self, data: &ProfitInfo) -> ProfitInfo {
-        if self.id != data.id {
-            godot_error!("ProfitInfo id not equal:{}:{}", self.id, data.id);
-            return self.clone();
-        }
-        let quantity_a = self.quantity;
-        let quantity_b = data.quantity;
-        let total_quantity = self.quantity + data.quantity;
-        let e = data.single_value();
-        let mut res = self.clone().single_value();
-
-        res.market_profit = res.market_profit.combine(e.market_profit);
-        res.avg_bazaar_profit = res.avg_bazaar_profit.combine(e.avg_bazaar_profit);
-        res.final_profit = res.final_profit.combine(e.final_profit);
-        if let Some(office_a) = res.office_profit.clone()
-            && let Some(office_b) = e.office_profit.clone()
-        {
-            res.office_profit = Some(office_a.combine(office_b));
-        }
+        let quantity = data.iter().map(|x| x.quantity).sum::<i32>();
+        let total_recyle_value = data.iter().map(|x| x.total_recyle_value).sum::<u64>();
 
-        res.market_profit = res.market_profit.build(total_quantity);
-        res.avg_bazaar_profit = res.avg_bazaar_profit.build(total_quantity);
-        res.final_profit = res.final_profit.build(total_quantity);
-        res.office_profit = res.office_profit.map(|item| item.build(total_quantity));
+        let offices: Vec<(&ProfitMetrics, i32)> = data
+            .iter()
+            .filter_map(|x| x.office_profit.as_ref().map(|o| (o, x.quantity)))
+            .collect();
+        let office_quantity = offices.iter().map(|x| x.1).sum::<i32>();
+        let offices: Vec<&ProfitMetrics> = offices.into_iter().map(|x| x.0).collect();
 
-        res.quantity = total_quantity;
-        res.single_recyle_price = (res.single_recyle_price * quantity_a as u64
-            + e.single_recyle_price * quantity_b as u64)
-            / (quantity_a as u64 + quantity_b as u64);
-        res
+        let mut res = first.clone();
+        res.quantity = quantity;
+        res.total_recyle_value = total_recyle_value;
+        res.single_recyle_price = div_round(total_recyle_value as i64, quantity) as u64;
+        res.created_on = data.iter().map(|x| x.created_on).min().unwrap_or_default();
+        res.market_profit = ProfitMetrics::combine(
+            &data.iter().map(|x| &x.market_profit).collect::<Vec<_>>(),
+            quantity,
+        );
+        res.avg_bazaar_profit = ProfitMetrics::combine(
+            &data
+                .iter()
+                .map(|x| &x.avg_bazaar_profit)
+                .collect::<Vec<_>>(),
+            quantity,
+        );
+        res.final_profit = ProfitMetrics::combine(
+            &data.iter().map(|x| &x.final_profit).collect::<Vec<_>>(),
+            quantity,
+        );
+        res.office_profit = if offices.is_empty() {
+            None
+        } else {
+            Some(ProfitMetrics::combine(&offices, office_quantity))
+        };
+        let museums: Vec<(&ProfitMetrics, i32)> = data
+            .iter()
+            .filter_map(|x| x.museum_profit.as_ref().map(|o| (o, x.quantity)))
+            .collect();
+        let museum_quantity = museums.iter().map(|x| x.1).sum::<i32>();
+        let museums: Vec<&ProfitMetrics> = museums.into_iter().map(|x| x.0).collect();
+        res.museum_profit = if museums.is_empty() {
+            None
+        } else {
+            Some(ProfitMetrics::combine(&museums, museum_quantity))
+        };
+        Some(res)
+    }
+}
+
+/// 整数除法，四舍五入
+fn div_round(value: i64, quantity: i32) -> i64 {
+    let q = quantity.max(1) as i64;
+    if value >= 0 {
+        (value + q / 2) / q
+    } else {
+        (value - q / 2) / q
+    }
+}
+
+/// 净利润百分比，费用不低于售价时到手价为 0，百分比没有意义，返回 0
+fn net_percentage(net_diff: i64, net_price: i64) -> f32 {
+    if net_price <= 0 {
+        return 0.0;
     }
+    net_diff as f32 / net_price as f32 * 100.0
 }
 
 /// 利润计算结果
+/// 利润和百分比都是扣除出售费用后的净值，毛利润单独保存
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct ProfitMetrics {
-    /// 利润百分比
+    /// 利润百分比（净利润 / 到手价格）
     pub percentage: f32,
-    /// 单个利润
+    /// 单个净利润
     pub single_profit_value: i64,
-    /// 总利润
+    /// 总净利润
     pub total_profit_value: i64,
+    /// 单个毛利润（未扣除出售费用）
+    pub single_gross_profit_value: i64,
+    /// 总毛利润（未扣除出售费用）
+    pub total_gross_profit_value: i64,
     /// 二次出售
     /// 单个售价
     pub single_sell_price: u64,
@@ -359,39 +673,59 @@ pub struct ProfitMetrics {
     pub total_sell_price: u64,
     /// 是否在官方售卖
     pub is_office: bool,
+    /// 是否兑换博物馆点数
+    pub is_museum: bool,
 }
 
 impl ProfitMetrics {
-    fn single_value(&self, quantity: i32) -> Self {
+    /// 根据售价和渠道费用计算利润
+    fn new(
+        sell_price: u64,
+        fee: &ChannelFee,
+        user_price: u64,
+        quantity: i32,
+        is_office: bool,
+    ) -> Self {
+        let q = quantity as i64;
+        let net_price = fee.net_price(sell_price);
+        let net_diff = net_price as i64 - user_price as i64;
+        let gross_diff = sell_price as i64 - user_price as i64;
         Self {
-            percentage: self.percentage,
-            single_profit_value: self.single_profit_value,
-            total_profit_value: self.total_profit_value / quantity as i64,
-            single_sell_price: self.single_sell_price,
-            total_sell_price: self.total_sell_price / quantity as u64,
-            is_office: self.is_office,
+            percentage: net_percentage(net_diff, net_price as i64),
+            single_profit_value: net_diff,
+            total_profit_value: net_diff * q,
+            single_gross_profit_value: gross_diff,
+            total_gross_profit_value: gross_diff * q,
+            single_sell_price: sell_price,
+            total_sell_price: sell_price * q as u64,
+            is_office,
+            is_museum: false,
         }
     }
 
-    fn combine(&self, data: Self) -> Self {
-        Self {
-            percentage: (self.percentage + data.percentage) / 2.,
-            single_profit_value: (self.single_profit_value + data.single_profit_value) / 2,
-            total_profit_value: (self.total_profit_value + data.total_profit_value) / 2,
-            single_sell_price: (self.single_sell_price + data.single_sell_price) / 2,
-            total_sell_price: (self.total_sell_price + data.total_sell_price) / 2,
-            is_office: self.is_office || data.is_office,
-        }
+    /// 总出售费用
+    pub fn total_fee(&self) -> i64 {
+        self.total_gross_profit_value - self.total_profit_value
     }
 
-    fn build(&self, quantity: i32) -> Self {
+    /// 按数量加权合并，总数直接相加，单个值和百分比由总数计算
+    fn combine(data: &[&ProfitMetrics], quantity: i32) -> Self {
+        let total_profit_value = data.iter().map(|x| x.total_profit_value).sum::<i64>();
+        let total_gross_profit_value = data.iter().map(|x| x.total_gross_profit_value).sum::<i64>();
+        let total_sell_price = data.iter().map(|x| x.total_sell_price).sum::<u64>();
+        // 到手总价 = 总售价 - 总费用
+        let total_net_sell_price =
+            total_sell_price as i64 - (total_gross_profit_value - total_profit_value);
         Self {
-            percentage: self.percentage,
-            single_profit_value: self.single_profit_value,
-            total_profit_value: self.total_profit_value * quantity as i64,
-            single_sell_price: self.single_sell_price,
-            total_sell_price: self.total_sell_price * quantity as u64,
-            is_office: self.is_office,
+            percentage: net_percentage(total_profit_value, total_net_sell_price),
+            single_profit_value: div_round(total_profit_value, quantity),
+            total_profit_value,
+            single_gross_profit_value: div_round(total_gross_profit_value, quantity),
+            total_gross_profit_value,
+            single_sell_price: div_round(total_sell_price as i64, quantity) as u64,
+            total_sell_price,
+            is_office: data.iter().any(|x| x.is_office),
+            is_museum: data.iter().any(|x| x.is_museum),
         }
     }
 }
@@ -405,7 +739,7 @@ struct SelectedProfit {
     office: Option<ProfitMetrics>,
 }
 
-/// 统一的利润计算函数
+/// 统一的利润计算函数，售价先按渠道扣除出售费用再计算利润
 /// - 在 target_ids 中：使用 market_price 和 avg_bazaar_price 计算利润，取较低的百分比
 /// - 不在 target_ids 中：使用官方价格计算利润，如果没有官方价格则返回 None
 fn compute_profit(
@@ -415,36 +749,32 @@ fn compute_profit(
     market_price: i64,
     avg_bazaar_price: i64,
     office_sell_price: Option<i64>,
+    fees: &ChannelFees,
 ) -> SelectedProfit {
-    let q = quantity as i64;
-
-    let market_diff = market_price - user_price as i64;
-    let market = ProfitMetrics {
-        percentage: market_diff as f32 / market_price as f32 * 100.0,
-        single_profit_value: market_diff,
-        total_profit_value: market_diff * q,
-        single_sell_price: market_price as u64,
-        total_sell_price: market_price as u64 * q as u64,
-        is_office: false,
-    };
+    let market = ProfitMetrics::new(
+        market_price as u64,
+        fees.get(SellChannel::Market),
+        user_price,
+        quantity,
+        false,
+    );
 
-    let bazaar_diff = avg_bazaar_price - user_price as i64;
-    let bazaar = ProfitMetrics {
-        percentage: bazaar_diff as f32 / avg_bazaar_price as f32 * 100.0,
-        single_profit_value: bazaar_diff,
-        total_profit_value: bazaar_diff * q,
-        single_sell_price: avg_bazaar_price as u64,
-        total_sell_price: avg_bazaar_price as u64 * q as u64,
-        is_office: false,
-    };
+    let bazaar = ProfitMetrics::new(
+        avg_bazaar_price as u64,
+        fees.get(SellChannel::Bazaar),
+        user_price,
+        quantity,
+        false,
+    );
 
-    let office = office_sell_price.map(|office_price| ProfitMetrics {
-        percentage: (office_price - user_price as i64) as f32 / office_price as f32 * 100.0,
-        single_profit_value: office_price - user_price as i64,
-        total_profit_value: (office_price - user_price as i64) * q,
-        single_sell_price: office_price as u64,
-        total_sell_price: office_price as u64 * q as u64,
-        is_office: true,
+    let office = office_sell_price.map(|office_price| {
+        ProfitMetrics::new(
+            office_price as u64,
+            fees.get(SellChannel::Office),
+            user_price,
+            quantity,
+            true,
+        )
     });
 
     let mut pick_market = if market.percentage <= bazaar.percentage {
@@ -505,20 +835,101 @@ pub struct Filter {
     pub office_sell_profit: u64,
     /// 官方售卖价格列表
     pub office_item_map: HashMap<i32, ItemInfo>,
+    /// 最低可信度，0 表示不限制
+    pub min_confidence: f32,
+    /// 玩家屏蔽、收藏和备注
+    pub players: PlayerList,
+    /// 武器和防具的处理方式
+    pub equipment_mode: EquipmentMode,
+}
+
+/// 武器和防具的价值取决于品质和加成，默认不参与利润计算
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EquipmentMode {
+    /// 不计算利润
+    #[default]
+    Exclude,
+    /// 只按基础价值计算，结果单独标记
+    BaseValue,
+}
+
+impl Filter {
+    /// 获取单个物品过滤条件
+    pub fn get_filter_item(&self, id: i32) -> Option<&FilterItem> {
+        self.filter_items.iter().find(|x| x.id == id)
+    }
 }
 
 /// 单个物品过滤条件
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct FilterItem {
     pub id: i32,
     pub name: String,
+    /// 最高购买价格，0 表示不限制
     pub price: u64,
-    pub profit_percentage: f32,
+    /// 最小利润百分比，None 时使用全局配置
+    pub profit_percentage: Option<f32>,
 }
 
-#[derive(Debug, Clone, Default)]
+impl FilterItem {
+    /// 从文本解析，每行一个物品：`id,最高价格,最小利润百分比,名称`
+    /// 价格为空或 0 表示不限制，百分比为空表示使用全局配置，名称可省略
+    pub fn parse_lines(text: &str) -> Vec<FilterItem> {
+        text.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                if line.is_empty() {
+                    return None;
+                }
+                let mut parts = line.splitn(4, ',').map(|x| x.trim());
+                let id = parts.next()?.parse::<i32>().ok()?;
+                let price = parts
+                    .next()
+                    .and_then(|x| x.parse::<u64>().ok())
+                    .unwrap_or_default();
+                let profit_percentage = parts.next().and_then(|x| x.parse::<f32>().ok());
+                let name = parts.next().unwrap_or_default().to_string();
+                Some(FilterItem {
+                    id,
+                    name,
+                    price,
+                    profit_percentage,
+                })
+            })
+            .collect()
+    }
+
+    /// 转换为文本，与 parse_lines 对应
+    pub fn format_lines(items: &[FilterItem]) -> String {
+        items
+            .iter()
+            .map(|x| {
+                let profit_percentage = x
+                    .profit_percentage
+                    .map(|p| p.to_string())
+                    .unwrap_or_default();
+                format!("{},{},{},{}", x.id, x.price, profit_percentage, x.name)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct SortProfitParams {
+    /// 多少秒内出现的卖家视为最近
     pub recent_sec: u64,
+    /// 排序条件，按顺序比较
+    pub keys: Vec<SortKey>,
+}
+
+impl Default for SortProfitParams {
+    fn default() -> Self {
+        Self {
+            recent_sec: 0,
+            keys: SortKey::parse_list(sort::SORT_PRESETS[0].1),
+        }
+    }
 }
 
 impl ContentHashable for ProfitInfo {
@@ -530,6 +941,12 @@ impl ContentHashable for ProfitInfo {
         hasher.write_u64(self.single_recyle_price);
         hasher.write_str(&self.image);
         hasher.write_u64(self.created_on);
+        hasher.write_f32(self.confidence.score);
+        hasher.write_u64(match self.equipment {
+            None => 0,
+            Some(EquipmentKind::Weapon) => 1,
+            Some(EquipmentKind::Armor) => 2,
+        });
 
         let final_profit_hash = self.final_profit.content_hash().0;
         hasher.write_u64(final_profit_hash);
@@ -544,9 +961,12 @@ impl ContentHashable for ProfitMetrics {
         hasher.write_f32(self.percentage);
         hasher.write_i64(self.single_profit_value);
         hasher.write_i64(self.total_profit_value);
+        hasher.write_i64(self.single_gross_profit_value);
+        hasher.write_i64(self.total_gross_profit_value);
         hasher.write_u64(self.single_sell_price);
         hasher.write_u64(self.total_sell_price);
         hasher.write_u64(if self.is_office { 1 } else { 0 });
+        hasher.write_u64(if self.is_museum { 1 } else { 0 });
         hasher.finish()
     }
 }
@@ -559,6 +979,10 @@ impl ContentHashable for ProfitUserInfo {
         hasher.write_u64(self.total_recyle_price);
         hasher.write_i64(self.total_profit_price);
         hasher.write_f32(self.profit_percentage);
+        hasher.write_f32(self.confidence);
+        hasher.write_u64(if self.is_favorite { 1 } else { 0 });
+        hasher.write_u64(if self.is_pinned { 1 } else { 0 });
+        hasher.write_str(&self.note);
         hasher.write_u64(self.created_on);
 
         let items_hash = self.items.content_hash().0;
@@ -567,3 +991,392 @@ impl ContentHashable for ProfitUserInfo {
         hasher.finish()
     }
 }
+
+#[cfg(test)]
+mod profit_test {
+    use super::*;
+    use crate::test_util::{production, profit_info, quote};
+
+    #[test]
+    fn test_filter_item_override() {
+        let filter = Filter {
+            target_ids: vec![1, 2, 3],
+            min_profit_percentage: 5.0,
+            filter_items: vec![
+                FilterItem {
+                    id: 1,
+                    price: 1000,
+                    ..Default::default()
+                },
+                FilterItem {
+                    id: 2,
+                    profit_percentage: Some(1.0),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let data = vec![
+            profit_info(0, 1, 1, 1200, 100_000, 10.0),
+            profit_info(0, 1, 1, 900, 100_000, 10.0),
+            profit_info(0, 2, 1, 900, 100_000, 2.0),
+            profit_info(0, 3, 1, 900, 100_000, 2.0),
+            profit_info(0, 3, 1, 900, 100_000, 6.0),
+        ];
+        let data = FavoritesData::filter_listing_price(data, &filter);
+        let res = FavoritesData::filter(data, filter);
+        let res: Vec<(i32, u64, f32)> = res
+            .iter()
+            .map(|x| (x.id, x.single_recyle_price, x.final_profit.percentage))
+            .collect();
+        assert_eq!(res, vec![(1, 900, 10.0), (2, 900, 2.0), (3, 900, 6.0)]);
+    }
+
+    #[test]
+    fn test_compute_profit_with_fee() {
+        let fees = ChannelFees {
+            market: ChannelFee {
+                percentage: 10.0,
+                flat: 0,
+            },
+            ..Default::default()
+        };
+        let selected = compute_profit(true, 800, 2, 1000, 1000, None, &fees);
+        assert_eq!(selected.market.single_gross_profit_value, 200);
+        assert_eq!(selected.market.single_profit_value, 100);
+        assert_eq!(selected.market.total_profit_value, 200);
+        assert_eq!(selected.market.total_fee(), 200);
+        assert_eq!(selected.bazaar.total_profit_value, 400);
+
+        let final_profit = selected.final_profit.unwrap();
+        assert_eq!(final_profit, selected.market);
+        assert!((final_profit.percentage - 100.0 / 900.0 * 100.0).abs() < 1e-4);
+
+        // 固定费用超过售价时到手价为 0，百分比为 0 而不是很大的负数
+        let fee = ChannelFee {
+            percentage: 0.0,
+            flat: 2000,
+        };
+        let metrics = ProfitMetrics::new(1000, &fee, 800, 1, false);
+        assert_eq!(metrics.single_profit_value, -800);
+        assert_eq!(metrics.percentage, 0.0);
+        let combined = ProfitMetrics::combine(&[&metrics, &metrics], 2);
+        assert_eq!(combined.percentage, 0.0);
+    }
+
+    fn listing(quantity: i32, price: u64, market_price: i64) -> ProfitInfo {
+        let selected = compute_profit(
+            true,
+            price,
+            quantity,
+            market_price,
+            market_price,
+            None,
+            &ChannelFees {
+                market: ChannelFee {
+                    percentage: 3.0,
+                    flat: 0,
+                },
+                ..Default::default()
+            },
+        );
+        ProfitInfo {
+            player_id: 1,
+            id: 206,
+            quantity,
+            single_recyle_price: price,
+            total_recyle_value: price * quantity as u64,
+            market_profit: selected.market,
+            avg_bazaar_profit: selected.bazaar,
+            final_profit: selected.final_profit.unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_combine_weighted() {
+        let data = vec![listing(1, 500, 1000), listing(99, 900, 1000)];
+        let res = FavoritesData::combine(data);
+        assert_eq!(res.len(), 1);
+        let res = &res[0];
+        assert_eq!(res.quantity, 100);
+        assert_eq!(res.total_recyle_price(), 500 + 99 * 900);
+        assert_eq!(res.single_recyle_price, 896);
+        // 到手价 970，净利润 470 + 99 * 70
+        assert_eq!(res.final_profit.total_profit_value, 470 + 99 * 70);
+        assert_eq!(res.final_profit.single_profit_value, 74);
+        assert_eq!(res.final_profit.total_gross_profit_value, 500 + 99 * 100);
+        assert_eq!(res.final_profit.total_sell_price, 100_000);
+        let percentage = (470 + 99 * 70) as f32 / 97_000.0 * 100.0;
+        assert!((res.final_profit.percentage - percentage).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_filter_listing_price() {
+        // 同一卖家的两个挂单只有一个超过最高价格，合并前过滤只去掉超过的挂单
+        let filter = Filter {
+            filter_items: vec![FilterItem {
+                id: 206,
+                price: 1000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let data = vec![listing(1, 800, 1000), listing(3, 1100, 1200)];
+        let data = FavoritesData::filter_listing_price(data, &filter);
+        let res = FavoritesData::combine(data);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].quantity, 1);
+        assert_eq!(res[0].single_recyle_price, 800);
+    }
+
+    #[test]
+    fn test_combine_order_independent() {
+        let data = vec![
+            listing(1, 500, 1000),
+            listing(7, 930, 1000),
+            listing(99, 900, 1000),
+            listing(3, 1010, 1000),
+        ];
+        let expected = FavoritesData::combine(data.clone());
+        let mut reversed = data.clone();
+        reversed.reverse();
+        assert_eq!(FavoritesData::combine(reversed), expected);
+        let rotated = [&data[2..], &data[..2]].concat();
+        assert_eq!(FavoritesData::combine(rotated), expected);
+    }
+
+    #[test]
+    fn test_filter_item_lines() {
+        let items = FilterItem::parse_lines("206,850000,2.5,Xanax\n\n 260 , 0 ,, \nabc,1,1\n186");
+        assert_eq!(
+            items,
+            vec![
+                FilterItem {
+                    id: 206,
+                    name: "Xanax".to_string(),
+                    price: 850000,
+                    profit_percentage: Some(2.5),
+                },
+                FilterItem {
+                    id: 260,
+                    ..Default::default()
+                },
+                FilterItem {
+                    id: 186,
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(
+            FilterItem::parse_lines(&FilterItem::format_lines(&items)),
+            items
+        );
+    }
+
+    #[test]
+    fn test_confidence_filter_and_sort() {
+        let mut low = profit_info(1, 1, 1, 900, 100_000, 10.0);
+        low.confidence.score = 40.0;
+        let mut high = profit_info(2, 2, 1, 900, 50_000, 10.0);
+        high.confidence.score = 90.0;
+
+        let filter = Filter {
+            target_ids: vec![1, 2],
+            min_confidence: 50.0,
+            ..Default::default()
+        };
+        let res = FavoritesData::filter(vec![low.clone(), high.clone()], filter);
+        assert_eq!(res.iter().map(|x| x.id).collect::<Vec<i32>>(), vec![2]);
+
+        let users = FavoritesData::calc_user_profit(vec![low, high]);
+        assert_eq!(users[0].confidence, 40.0);
+        let mut params = SortProfitParams::default();
+        let sorted = FavoritesData::sort_profit(params.clone(), users.clone());
+        assert_eq!(sorted[0].player_id, 1);
+        params.keys = SortKey::parse_list("confidence:desc,profit:desc");
+        let sorted = FavoritesData::sort_profit(params, users);
+        assert_eq!(sorted[0].player_id, 2);
+    }
+
+    #[test]
+    fn test_player_block_and_favorite() {
+        let players = PlayerList::parse_lines("1,block\n3,favorite,reliable");
+        let data: Vec<ProfitInfo> = [(1, 300_000), (2, 200_000), (3, 100_000)]
+            .iter()
+            .map(|(player_id, profit)| profit_info(*player_id, 206, 1, 900, *profit, 10.0))
+            .collect();
+        let filter = Filter {
+            target_ids: vec![206],
+            players: players.clone(),
+            ..Default::default()
+        };
+        let res = FavoritesData::filter(data, filter);
+        let mut users = FavoritesData::calc_user_profit(res);
+        FavoritesData::mark_players(&players, &mut users);
+        let sorted = FavoritesData::sort_profit(SortProfitParams::default(), users);
+        let res: Vec<(i32, bool, &str)> = sorted
+            .iter()
+            .map(|x| (x.player_id, x.is_favorite, x.note.as_str()))
+            .collect();
+        assert_eq!(res, vec![(3, true, "reliable"), (2, false, "")]);
+    }
+
+    #[test]
+    fn test_alert_pin() {
+        let mut data = FavoritesData {
+            alerts: AlertEngine::new(crate::alert::AlertRule::parse_lines(
+                "big|profit>150000|pin|0",
+            )),
+            ..Default::default()
+        };
+        let users: Vec<ProfitUserInfo> = [(1, 300_000), (2, 200_000), (3, 100_000)]
+            .iter()
+            .map(|(player_id, profit)| {
+                let item = profit_info(*player_id, 206, 1, 900, *profit, 10.0);
+                FavoritesData::calc_user_profit(vec![item]).remove(0)
+            })
+            .collect();
+        data.alert_events = data.alerts.evaluate(&users[1..], 100);
+        let users = data.pin_users(users);
+        let users = FavoritesData::pinned_first(users);
+        let res: Vec<(i32, bool)> = users.iter().map(|x| (x.player_id, x.is_pinned)).collect();
+        assert_eq!(res, vec![(2, true), (1, false), (3, false)]);
+
+        // 卖家消失后取消置顶
+        data.alert_events.clear();
+        let users = data.pin_users(users.into_iter().filter(|x| x.player_id != 2).collect());
+        assert!(users.iter().all(|x| !x.is_pinned));
+        assert!(data.pinned.is_empty());
+    }
+
+    #[test]
+    fn test_item_market_ask() {
+        let now = tools::time::get_current_time();
+        let mut data = FavoritesData {
+            filter: Filter {
+                target_ids: vec![206],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let production = quote(QuoteSource::Weav3rBazaar, Some(1), 900, now).to_production();
+        let profit = |data: &FavoritesData| {
+            let res = data.product_to_profit_info(production.clone());
+            (
+                res[0].market_profit.total_profit_value,
+                res[0].item_market_ask,
+            )
+        };
+        let (base, ask) = profit(&data);
+        assert_eq!(ask, None);
+
+        // 物品市场挂单更低时按挂单价计算
+        data.set_quotes(vec![quote(QuoteSource::ItemMarket, None, 950, now)]);
+        let (capped, ask) = profit(&data);
+        assert_eq!(ask, Some(950));
+        assert_eq!(base - capped, 50);
+
+        // 过期的报价不参与计算
+        let stale = now - FavoritesData::ITEM_MARKET_MAX_AGE_SEC - 1;
+        data.set_quotes(vec![quote(QuoteSource::ItemMarket, None, 950, stale)]);
+        assert_eq!(profit(&data), (base, None));
+
+        // bazaar 报价直接重新计算利润
+        data.set_quotes(vec![quote(QuoteSource::Weav3rBazaar, Some(1), 900, now)]);
+        assert_eq!(data.user_profit_result.len(), 1);
+    }
+
+    #[test]
+    fn test_museum_channel() {
+        // 花卉套装 11 种，其中一种 400，其余 50
+        let productions: Vec<ProductionItem> = museum::FLOWER_SET
+            .item_ids
+            .iter()
+            .map(|id| production(*id, 410, &[(1, if *id == 385 { 400 } else { 50 })]))
+            .collect();
+        let mut data = FavoritesData {
+            filter: Filter {
+                target_ids: vec![385],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(FavoritesData::museum_values(0, &productions).is_empty());
+
+        // 一套 10 点，点数 100，物品 385 的边际价值 = 1000 - 50 * 10
+        data.museum_values = FavoritesData::museum_values(100, &productions);
+        assert_eq!(data.museum_values.get(&385), Some(&500));
+        let res = data.product_to_profit_info(productions[9].clone());
+        let museum = res[0].museum_profit.clone().unwrap();
+        assert_eq!(museum.single_profit_value, 100);
+        assert!(res[0].final_profit.is_museum);
+
+        // 只有一个计入博物馆渠道，剩余的按市场计算
+        let mut item = productions[9].clone();
+        item.cheapest_bazaars[0].quantity = 3;
+        let res = data.product_to_profit_info(item);
+        let res: Vec<(i32, bool, bool)> = res
+            .iter()
+            .map(|x| {
+                (
+                    x.quantity,
+                    x.museum_profit.is_some(),
+                    x.final_profit.is_museum,
+                )
+            })
+            .collect();
+        assert_eq!(res, vec![(1, true, true), (2, false, false)]);
+
+        // 点数价格较低时市场利润更高
+        data.museum_values = FavoritesData::museum_values(91, &productions);
+        let res = data.product_to_profit_info(productions[9].clone());
+        assert_eq!(
+            res[0].museum_profit.as_ref().unwrap().single_profit_value,
+            10
+        );
+        assert!(!res[0].final_profit.is_museum);
+    }
+
+    #[test]
+    fn test_equipment_mode() {
+        let production = |id: i32| ProductionItem {
+            avg_bazaar_price: Some(1800),
+            ..production(id, 2000, &[(1, 1000)])
+        };
+        let item = |id: i32, t: &str, weapon_type: &str| ItemInfo {
+            id,
+            t: t.to_string(),
+            weapon_type: weapon_type.to_string(),
+            market_value: 1500,
+            tradeable: true,
+            ..Default::default()
+        };
+        let mut data = FavoritesData {
+            filter: Filter {
+                target_ids: vec![1, 2, 3],
+                office_item_map: HashMap::from([
+                    (1, item(1, "Melee", "Clubbing")),
+                    (2, item(2, "Defensive", "")),
+                    (3, item(3, "Drug", "")),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 默认不计算武器和防具
+        assert!(data.product_to_profit_info(production(1)).is_empty());
+        assert!(data.product_to_profit_info(production(2)).is_empty());
+        let res = data.product_to_profit_info(production(3));
+        assert_eq!(res[0].equipment, None);
+        assert_eq!(res[0].final_profit.single_profit_value, 800);
+
+        // 基础价值取市场价、bazaar 均价和目录价值中最低的
+        data.filter.equipment_mode = EquipmentMode::BaseValue;
+        let res = data.product_to_profit_info(production(1));
+        assert_eq!(res[0].equipment, Some(EquipmentKind::Weapon));
+        assert_eq!(res[0].final_profit.single_profit_value, 500);
+        let res = data.product_to_profit_info(production(2));
+        assert_eq!(res[0].equipment, Some(EquipmentKind::Armor));
+    }
+}
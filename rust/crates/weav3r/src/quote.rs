@@ -0,0 +1,214 @@
+use model::{
+    error::MyError,
+    torn::{api::parse_torn_response, item_market::ItemMarketResponse},
+    weav3r::favorites::{BazaarPriceInfo, FavoritesResponse, ProductionItem},
+};
+
+/// 报价来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuoteSource {
+    /// weav3r 收藏页面的 bazaar 挂单
+    Weav3rBazaar,
+    /// Torn API 物品市场挂单
+    ItemMarket,
+}
+
+/// 单个挂单，物品市场挂单没有卖家信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteListing {
+    pub player_id: Option<i32>,
+    pub player_name: String,
+    pub quantity: i32,
+    pub price: u64,
+}
+
+/// 统一的报价结构，不同来源的数据都转换成这个结构
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceQuote {
+    pub source: QuoteSource,
+    pub item_id: i32,
+    pub name: String,
+    pub image: String,
+    /// 来源给出的市场价
+    pub market_price: Option<i64>,
+    /// 来源给出的平均价
+    pub average_price: Option<i64>,
+    /// 按价格从低到高排列
+    pub listings: Vec<QuoteListing>,
+    pub fetched_at: u64,
+}
+
+impl PriceQuote {
+    /// 最低挂单价格
+    pub fn lowest_ask(&self) -> Option<u64> {
+        self.listings.iter().map(|x| x.price).min()
+    }
+
+    /// 转换为 weav3r 的物品结构，只保留有卖家信息的挂单
+    pub fn to_production(&self) -> ProductionItem {
+        ProductionItem {
+            id: self.item_id,
+            name: self.name.clone(),
+            image: self.image.clone(),
+            market_price: self.market_price,
+            avg_bazaar_price: self.average_price,
+            cheapest_bazaars: self
+                .listings
+                .iter()
+                .filter_map(|x| {
+                    Some(BazaarPriceInfo {
+                        player_id: x.player_id?,
+                        player_name: x.player_name.clone(),
+                        quantity: x.quantity,
+                        price: x.price as i64,
+                        total_value: (x.price * x.quantity.max(0) as u64).to_string(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 报价数据源，负责把接口返回的文本解析为统一的报价
+pub trait PriceSource {
+    fn source(&self) -> QuoteSource;
+
+    fn parse_quotes(&self, text: &str, now: u64) -> Result<Vec<PriceQuote>, MyError>;
+}
+
+/// weav3r 收藏页面
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Weav3rFavoritesSource;
+
+impl PriceSource for Weav3rFavoritesSource {
+    fn source(&self) -> QuoteSource {
+        QuoteSource::Weav3rBazaar
+    }
+
+    fn parse_quotes(&self, text: &str, now: u64) -> Result<Vec<PriceQuote>, MyError> {
+        let response = FavoritesResponse::from_text(text)?;
+        Ok(response
+            .items
+            .into_iter()
+            .map(|item| {
+                let mut listings: Vec<QuoteListing> = item
+                    .cheapest_bazaars
+                    .into_iter()
+                    .map(|x| QuoteListing {
+                        player_id: Some(x.player_id),
+                        player_name: x.player_name,
+                        quantity: x.quantity,
+                        price: x.price.max(0) as u64,
+                    })
+                    .collect();
+                listings.sort_by_key(|x| x.price);
+                PriceQuote {
+                    source: self.source(),
+                    item_id: item.id,
+                    name: item.name,
+                    image: item.image,
+                    market_price: item.market_price,
+                    average_price: item.avg_bazaar_price,
+                    listings,
+                    fetched_at: now,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Torn API v2 物品市场，每次请求一个物品
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TornItemMarketSource;
+
+impl TornItemMarketSource {
+    /// 解析物品市场响应，先解析错误结构，状态码不是 200 的错误响应也能识别出限流等错误
+    pub fn parse_response(
+        &self,
+        response_code: i64,
+        text: &str,
+        now: u64,
+    ) -> Result<Vec<PriceQuote>, MyError> {
+        let market = parse_torn_response::<ItemMarketResponse>(response_code, text)?.itemmarket;
+        let mut listings: Vec<QuoteListing> = market
+            .listings
+            .into_iter()
+            .map(|x| QuoteListing {
+                player_id: None,
+                player_name: String::new(),
+                quantity: x.amount,
+                price: x.price.max(0) as u64,
+            })
+            .collect();
+        listings.sort_by_key(|x| x.price);
+        Ok(vec![PriceQuote {
+            source: self.source(),
+            item_id: market.item.id,
+            name: market.item.name,
+            image: String::new(),
+            market_price: market.item.average_price,
+            average_price: market.item.average_price,
+            listings,
+            fetched_at: now,
+        }])
+    }
+}
+
+impl PriceSource for TornItemMarketSource {
+    fn source(&self) -> QuoteSource {
+        QuoteSource::ItemMarket
+    }
+
+    fn parse_quotes(&self, text: &str, now: u64) -> Result<Vec<PriceQuote>, MyError> {
+        self.parse_response(200, text, now)
+    }
+}
+
+#[cfg(test)]
+mod quote_test {
+    use super::*;
+
+    #[test]
+    fn test_weav3r_favorites_source() {
+        let text = include_str!("../fixtures/weav3r_favorites.txt");
+        let quotes = Weav3rFavoritesSource.parse_quotes(text, 100).unwrap();
+        assert_eq!(quotes.len(), 2);
+        let quote = &quotes[0];
+        assert_eq!(quote.source, QuoteSource::Weav3rBazaar);
+        assert_eq!(quote.item_id, 385);
+        assert_eq!(quote.market_price, Some(66508));
+        assert_eq!(quote.average_price, Some(68090));
+        assert_eq!(quote.lowest_ask(), Some(66500));
+        assert_eq!(quote.listings[0].player_id, Some(2828142));
+        assert_eq!(quote.fetched_at, 100);
+
+        // 转换回 weav3r 结构后挂单不丢失
+        let production = quote.to_production();
+        assert_eq!(production.cheapest_bazaars.len(), quote.listings.len());
+        assert_eq!(production.cheapest_bazaars[0].player_name, "Conanskeptical");
+    }
+
+    #[test]
+    fn test_torn_item_market_source() {
+        let text = include_str!("../fixtures/torn_item_market.json");
+        let quotes = TornItemMarketSource.parse_quotes(text, 100).unwrap();
+        assert_eq!(quotes.len(), 1);
+        let quote = &quotes[0];
+        assert_eq!(quote.source, QuoteSource::ItemMarket);
+        assert_eq!(quote.item_id, 385);
+        assert_eq!(quote.name, "Tribulus Omanense");
+        assert_eq!(quote.average_price, Some(66800));
+        assert_eq!(quote.lowest_ask(), Some(66200));
+        assert_eq!(quote.listings.len(), 3);
+        assert!(quote.listings.iter().all(|x| x.player_id.is_none()));
+        // 没有卖家信息的挂单不能转换为 bazaar 挂单
+        assert!(quote.to_production().cheapest_bazaars.is_empty());
+
+        assert!(TornItemMarketSource.parse_quotes("{}", 100).is_err());
+        let busy = r#"{"error":{"code":5,"error":"Too many requests"}}"#;
+        assert!(matches!(
+            TornItemMarketSource.parse_response(429, busy, 100),
+            Err(MyError::TornTooManyRequests(_))
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+
+use crate::profit::{ProfitInfo, ProfitUserInfo};
+
+/// 排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    /// 净利润
+    Profit,
+    /// 利润百分比
+    Percentage,
+    /// 可信度
+    Confidence,
+    /// 需要的资金
+    Cash,
+    /// 最近出现，light_sec 内出现的卖家视为最近
+    Recency,
+    /// 收藏的卖家
+    Favorite,
+    /// 物品类型，按名称排序
+    ItemType,
+}
+
+impl SortField {
+    pub fn parse(field: &str) -> Option<Self> {
+        match field.trim().to_lowercase().as_str() {
+            "profit" => Some(SortField::Profit),
+            "percentage" => Some(SortField::Percentage),
+            "confidence" => Some(SortField::Confidence),
+            "cash" => Some(SortField::Cash),
+            "recent" => Some(SortField::Recency),
+            "favorite" => Some(SortField::Favorite),
+            "type" => Some(SortField::ItemType),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::Profit => "profit",
+            SortField::Percentage => "percentage",
+            SortField::Confidence => "confidence",
+            SortField::Cash => "cash",
+            SortField::Recency => "recent",
+            SortField::Favorite => "favorite",
+            SortField::ItemType => "type",
+        }
+    }
+}
+
+/// 排序条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn desc(field: SortField) -> Self {
+        Self {
+            field,
+            descending: true,
+        }
+    }
+
+    pub fn asc(field: SortField) -> Self {
+        Self {
+            field,
+            descending: false,
+        }
+    }
+
+    /// 从文本解析，格式：`字段:asc/desc,字段:asc/desc`，省略方向时为降序
+    pub fn parse_list(text: &str) -> Vec<SortKey> {
+        text.split(',')
+            .filter(|x| !x.trim().is_empty())
+            .filter_map(|x| {
+                let (field, direction) = x.split_once(':').unwrap_or((x, "desc"));
+                let Some(field) = SortField::parse(field) else {
+                    log::warn!("SortKey: invalid sort field: {}", x);
+                    return None;
+                };
+                Some(SortKey {
+                    field,
+                    descending: direction.trim().to_lowercase() != "asc",
+                })
+            })
+            .collect()
+    }
+
+    /// 转换为文本，与 parse_list 对应
+    pub fn format_list(keys: &[SortKey]) -> String {
+        keys.iter()
+            .map(|x| {
+                format!(
+                    "{}:{}",
+                    x.field.as_str(),
+                    if x.descending { "desc" } else { "asc" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    fn apply(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// 预设的排序方式：名称，排序条件
+pub const SORT_PRESETS: [(&str, &str); 5] = [
+    ("Recent", "recent:desc,favorite:desc,profit:desc"),
+    ("Profit", "favorite:desc,profit:desc"),
+    ("Percentage", "favorite:desc,percentage:desc,profit:desc"),
+    (
+        "Confidence",
+        "recent:desc,favorite:desc,confidence:desc,profit:desc",
+    ),
+    ("Low Cash", "cash:asc,profit:desc"),
+];
+
+/// 比较两个卖家，recent_since 之后出现的卖家视为最近
+pub fn compare_users(
+    keys: &[SortKey],
+    recent_since: u64,
+    a: &ProfitUserInfo,
+    b: &ProfitUserInfo,
+) -> Ordering {
+    keys.iter().fold(Ordering::Equal, |ordering, key| {
+        ordering.then_with(|| {
+            let res = match key.field {
+                SortField::Profit => a.total_profit_price.cmp(&b.total_profit_price),
+                SortField::Percentage => a.profit_percentage.total_cmp(&b.profit_percentage),
+                SortField::Confidence => a.confidence.total_cmp(&b.confidence),
+                SortField::Cash => a.total_recyle_price.cmp(&b.total_recyle_price),
+                SortField::Recency => {
+                    (a.created_on >= recent_since).cmp(&(b.created_on >= recent_since))
+                }
+                SortField::Favorite => a.is_favorite.cmp(&b.is_favorite),
+                // 卖家按第一个物品的类型排序
+                SortField::ItemType => user_item_type(a).cmp(user_item_type(b)),
+            };
+            key.apply(res)
+        })
+    })
+}
+
+/// 比较同一卖家的两个物品，卖家维度的条件(最近、收藏)不影响物品顺序
+pub fn compare_items(keys: &[SortKey], a: &ProfitInfo, b: &ProfitInfo) -> Ordering {
+    keys.iter().fold(Ordering::Equal, |ordering, key| {
+        ordering.then_with(|| {
+            let res = match key.field {
+                SortField::Profit => a
+                    .final_profit
+                    .total_profit_value
+                    .cmp(&b.final_profit.total_profit_value),
+                SortField::Percentage => a
+                    .final_profit
+                    .percentage
+                    .total_cmp(&b.final_profit.percentage),
+                SortField::Confidence => a.confidence.score.total_cmp(&b.confidence.score),
+                SortField::Cash => a.total_recyle_price().cmp(&b.total_recyle_price()),
+                SortField::Recency | SortField::Favorite => Ordering::Equal,
+                SortField::ItemType => a.item_type.cmp(&b.item_type),
+            };
+            key.apply(res)
+        })
+    })
+}
+
+fn user_item_type(user: &ProfitUserInfo) -> &str {
+    user.items
+        .first()
+        .map(|x| x.item_type.as_str())
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod sort_test {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let keys = SortKey::parse_list("recent, cash:asc,unknown:desc, type:ASC,");
+        assert_eq!(
+            keys,
+            vec![
+                SortKey::desc(SortField::Recency),
+                SortKey::asc(SortField::Cash),
+                SortKey::asc(SortField::ItemType),
+            ]
+        );
+        assert_eq!(SortKey::format_list(&keys), "recent:desc,cash:asc,type:asc");
+        for (_, preset) in SORT_PRESETS {
+            assert_eq!(SortKey::format_list(&SortKey::parse_list(preset)), preset);
+        }
+    }
+
+    #[test]
+    fn test_compare_users() {
+        let user = |player_id: i32, profit: i64, cash: u64, created_on: u64| ProfitUserInfo {
+            player_id,
+            total_profit_price: profit,
+            total_recyle_price: cash,
+            created_on,
+            ..Default::default()
+        };
+        let mut users = [
+            user(1, 100, 5000, 10),
+            user(2, 300, 9000, 10),
+            user(3, 200, 1000, 100),
+        ];
+
+        let keys = SortKey::parse_list(SORT_PRESETS[0].1);
+        users.sort_by(|a, b| compare_users(&keys, 50, a, b));
+        let ids: Vec<i32> = users.iter().map(|x| x.player_id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+
+        let keys = SortKey::parse_list("cash:asc");
+        users.sort_by(|a, b| compare_users(&keys, 50, a, b));
+        let ids: Vec<i32> = users.iter().map(|x| x.player_id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+    }
+}
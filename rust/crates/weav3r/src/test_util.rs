@@ -0,0 +1,88 @@
+use model::weav3r::favorites::{BazaarPriceInfo, ProductionItem};
+
+use crate::{
+    profit::{ProfitInfo, ProfitMetrics, ProfitUserInfo},
+    quote::{PriceQuote, QuoteListing, QuoteSource},
+};
+
+/// 物品和挂单，listings 为 (卖家, 价格)，每个挂单数量为 1，bazaar 均价和市场价相同
+pub fn production(id: i32, market_price: i64, listings: &[(i32, i64)]) -> ProductionItem {
+    ProductionItem {
+        id,
+        name: format!("item-{}", id),
+        image: String::new(),
+        market_price: Some(market_price),
+        avg_bazaar_price: Some(market_price),
+        cheapest_bazaars: listings
+            .iter()
+            .map(|(player_id, price)| BazaarPriceInfo {
+                player_id: *player_id,
+                player_name: format!("player-{}", player_id),
+                quantity: 1,
+                price: *price,
+                total_value: price.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// 物品 206 的报价，只有一个挂单
+pub fn quote(
+    source: QuoteSource,
+    player_id: Option<i32>,
+    price: u64,
+    fetched_at: u64,
+) -> PriceQuote {
+    PriceQuote {
+        source,
+        item_id: 206,
+        name: "Xanax".to_string(),
+        image: String::new(),
+        market_price: Some(1000),
+        average_price: Some(1000),
+        listings: vec![QuoteListing {
+            player_id,
+            player_name: format!("player-{}", player_id.unwrap_or_default()),
+            quantity: 1,
+            price,
+        }],
+        fetched_at,
+    }
+}
+
+/// 单个挂单的利润，profit 为单个净利润
+pub fn profit_info(
+    player_id: i32,
+    id: i32,
+    quantity: i32,
+    price: u64,
+    profit: i64,
+    percentage: f32,
+) -> ProfitInfo {
+    ProfitInfo {
+        player_id,
+        player_name: format!("player-{}", player_id),
+        id,
+        name: format!("item-{}", id),
+        quantity,
+        single_recyle_price: price,
+        total_recyle_value: price * quantity.max(0) as u64,
+        final_profit: ProfitMetrics {
+            percentage,
+            single_profit_value: profit,
+            total_profit_value: profit * quantity as i64,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// 只有一个挂单的卖家
+pub fn user(item: ProfitInfo) -> ProfitUserInfo {
+    ProfitUserInfo {
+        player_id: item.player_id,
+        player_name: item.player_name.clone(),
+        items: vec![item],
+        ..Default::default()
+    }
+}